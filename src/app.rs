@@ -1,28 +1,60 @@
 use crate::{
-    enums::{Item, VaultItem},
+    enums::{CheckType, IndexAction, Item, TrashAction, VaultItem},
+    notes::{fuzzy::NoteMatch, links::LinkUpdate},
     output::{error::Error, message::Message},
     state::{
-        args::{Args, Command},
+        args::{Args, Command, VaultAction},
         config::Config,
-        vaults::Vaults,
+        journal::Operation,
+        trash::parse_age,
+        vaults::{
+            daily::{parse_date, parse_month},
+            doctor::Problem,
+            Vaults,
+        },
     },
     traits::FileIO,
+    utils::{confirm_fix, confirm_recovery, pick_note},
+};
+use chrono::{Datelike, Local, NaiveDate};
+use std::{
+    io::{stdin, IsTerminal},
+    path::Path,
 };
-use clap::Parser;
 
 pub struct App {
     args: Args,
+    interactive: bool,
     config: Config,
     vaults: Vaults,
 }
 
 impl App {
-    pub fn new() -> Self {
-        App {
-            args: Args::parse(),
-            config: Config::load(),
-            vaults: Vaults::load(),
-        }
+    // @notes:
+    //      -> Repairing data files that can't be parsed (and fixing problems found by doctor) is
+    //         only offered when there's a user to ask, i.e. not with JSON output or when stdin
+    //         isn't a terminal. The same goes for picking between notes matching a fuzzy open.
+    pub fn new(args: Args) -> Result<Self, Error> {
+        let interactive = !args.json && stdin().is_terminal();
+        let confirm = |error: &Error, backup: &Path| interactive && confirm_recovery(error, backup);
+
+        let config = Config::load(&confirm)?;
+        // Doctor checks the vaults itself, so it doesn't load the current one.
+        let vaults = match args.command {
+            Command::Doctor { .. } => Vaults::load_registry(&confirm, config.get_note_types())?,
+            _ => Vaults::load(
+                &confirm,
+                args.vault.as_deref().filter(|name| !name.is_empty()),
+                config.get_note_types(),
+            )?,
+        };
+
+        Ok(App {
+            args,
+            interactive,
+            config,
+            vaults,
+        })
     }
 
     pub fn handle_args(&mut self) -> Result<Message, Error> {
@@ -31,35 +63,84 @@ impl App {
                 show_loc,
                 name,
                 location,
+                action,
             } => {
-                if let (Some(name), Some(location)) = (name, location) {
+                if let Some(action) = action {
+                    match action {
+                        VaultAction::Add { name, path } => {
+                            self.vaults.add_vault(name, path)?;
+                            Ok(Message::VaultAdded(name.to_owned()))
+                        }
+                        VaultAction::Forget { name } => {
+                            self.vaults.forget_vault(name)?;
+                            Ok(Message::VaultForgotten(name.to_owned()))
+                        }
+                        VaultAction::Scan { root, depth } => {
+                            Ok(Message::VaultScan(self.vaults.scan_vaults(root, *depth)?))
+                        }
+                    }
+                } else if let (Some(name), Some(location)) = (name, location) {
                     self.vaults.create_vault(name, location)?;
                     Ok(Message::ItemCreated(Item::Vl, name.to_owned()))
                 } else {
-                    self.vaults.list_vaults(show_loc);
-                    Ok(Message::Empty)
+                    Ok(Message::Vaults(self.vaults.list_vaults(show_loc)))
                 }
             }
             Command::Enter { name } => {
                 self.vaults.enter_vault(name)?;
                 Ok(Message::VaultEntered(name.to_owned()))
             }
-            Command::Note { name } => {
-                self.vaults
-                    .ref_current()?
-                    .create_vault_item(VaultItem::Nt, name)?;
+            Command::Note {
+                name,
+                parents,
+                ext,
+                template,
+                vars,
+            } => {
+                let vault = self.vaults.ref_current()?;
+                let name = &match ext {
+                    Some(ext) => vault.get_note_types().add_extension(name, ext)?,
+                    None => name.to_owned(),
+                };
+
+                if let Some(template) = template.as_ref().or(self.config.get_default_template()) {
+                    vault.create_note_from_template(
+                        name,
+                        template,
+                        vars,
+                        &self.config.get_templates_dir(),
+                        *parents,
+                    )?;
+                } else {
+                    vault.create_vault_item(VaultItem::Nt, name, *parents)?;
+                }
+
                 Ok(Message::ItemCreated(Item::Nt, name.to_owned()))
             }
-            Command::Open { name } => {
-                self.vaults
-                    .ref_current()?
-                    .open_note(name, self.config.get_editor_data())?;
+            Command::Open { name, first, line } => {
+                let interactive = self.interactive;
+                let pick = |query: &str, candidates: &[NoteMatch]| {
+                    if *first {
+                        Some(0)
+                    } else if interactive {
+                        pick_note(query, candidates)
+                    } else {
+                        None
+                    }
+                };
+
+                self.vaults.mut_current()?.open_note(
+                    name,
+                    &self.config.get_editor(),
+                    *line,
+                    &pick,
+                )?;
                 Ok(Message::Empty)
             }
-            Command::Folder { name } => {
+            Command::Folder { name, parents } => {
                 self.vaults
                     .ref_current()?
-                    .create_vault_item(VaultItem::Fd, name)?;
+                    .create_vault_item(VaultItem::Fd, name, *parents)?;
                 Ok(Message::ItemCreated(Item::Fd, name.to_owned()))
             }
             Command::Opdir => {
@@ -70,49 +151,96 @@ impl App {
                 self.vaults.mut_current()?.change_folder(path)?;
                 Ok(Message::FolderChanged)
             }
+            Command::History => {
+                let history = self.vaults.ref_current()?.get_history().to_owned();
+                Ok(Message::History(history))
+            }
+            Command::Today | Command::Yesterday | Command::Tomorrow => {
+                let today = Local::now().date_naive();
+                let date = match self.args.command {
+                    Command::Yesterday => today.pred_opt().unwrap(),
+                    Command::Tomorrow => today.succ_opt().unwrap(),
+                    _ => today,
+                };
+
+                self.open_daily(&date)
+            }
+            Command::Daily { list, date } => {
+                if *list {
+                    let month = match date {
+                        Some(month) => parse_month(month)?,
+                        None => Local::now().date_naive().with_day(1).unwrap(),
+                    };
+
+                    let calendar = self.vaults.ref_current()?.list_daily(month);
+                    Ok(Message::DailyNotes(calendar))
+                } else {
+                    let date = parse_date(date.as_ref().unwrap())?;
+                    self.open_daily(&date)
+                }
+            }
             Command::Remove { item_type, name } => {
-                match item_type {
+                let id = match item_type {
                     Item::Vl | Item::Vault => self.vaults.remove_vault(name)?,
                     _ => self
                         .vaults
                         .ref_current()?
                         .remove_vault_item(item_type.to_vault_item(), name)?,
                 };
-                Ok(Message::ItemRemoved(item_type.to_owned(), name.to_owned()))
+                Ok(Message::ItemRemoved(
+                    item_type.to_owned(),
+                    name.to_owned(),
+                    id,
+                ))
             }
             Command::Rename {
                 item_type,
                 name,
                 new_name,
+                no_update_links,
             } => {
-                match item_type {
-                    Item::Vl | Item::Vault => self.vaults.rename_vault(name, new_name)?,
+                let links_updated = match item_type {
+                    Item::Vl | Item::Vault => {
+                        self.vaults.rename_vault(name, new_name)?;
+                        LinkUpdate::default()
+                    }
                     _ => self.vaults.ref_current()?.rename_vault_item(
                         item_type.to_vault_item(),
                         name,
                         new_name,
+                        !no_update_links,
                     )?,
                 };
                 Ok(Message::ItemRenamed(
                     item_type.to_owned(),
                     name.to_owned(),
                     new_name.to_owned(),
+                    links_updated,
                 ))
             }
             Command::Move {
                 item_type,
                 name,
                 new_location,
+                no_update_links,
             } => {
-                match item_type {
-                    Item::Vl | Item::Vault => self.vaults.move_vault(name, new_location)?,
+                let links_updated = match item_type {
+                    Item::Vl | Item::Vault => {
+                        self.vaults.move_vault(name, new_location)?;
+                        LinkUpdate::default()
+                    }
                     _ => self.vaults.ref_current()?.move_vault_item(
                         item_type.to_vault_item(),
                         name,
                         new_location,
+                        !no_update_links,
                     )?,
                 };
-                Ok(Message::ItemMoved(item_type.to_owned(), name.to_owned()))
+                Ok(Message::ItemMoved(
+                    item_type.to_owned(),
+                    name.to_owned(),
+                    links_updated,
+                ))
             }
             Command::Vmove {
                 item_type,
@@ -126,9 +254,91 @@ impl App {
                     vault_name.to_owned(),
                 ))
             }
-            Command::List { item_type } => {
-                self.vaults.ref_current()?.list(item_type);
-                Ok(Message::Empty)
+            Command::List { item_type, tag } => {
+                let tree = self.vaults.ref_current()?.list(item_type, tag);
+                Ok(Message::Tree(tree))
+            }
+            Command::Tags => {
+                let tags = self.vaults.ref_current()?.tags()?;
+                Ok(Message::Tags(tags))
+            }
+            Command::Backlinks { name } => {
+                let backlinks = self.vaults.ref_current()?.backlinks(name)?;
+                Ok(Message::Backlinks(backlinks))
+            }
+            Command::Check { check_type } => match check_type {
+                CheckType::Links => {
+                    let report = self.vaults.ref_current()?.check_links()?;
+
+                    if report.is_clean() {
+                        Ok(Message::LinksChecked)
+                    } else {
+                        Err(Error::LinkCheckFailed(report))
+                    }
+                }
+            },
+            Command::Meta {
+                name,
+                key,
+                value,
+                delete,
+            } => {
+                let vault = self.vaults.ref_current()?;
+
+                match (key, value) {
+                    (Some(key), _) if *delete => {
+                        vault.remove_meta_field(name, key)?;
+                        Ok(Message::MetaRemoved(key.to_owned()))
+                    }
+                    (Some(key), Some(value)) => {
+                        vault.set_meta_field(name, key, value)?;
+                        Ok(Message::MetaSet(key.to_owned(), value.to_owned()))
+                    }
+                    (Some(key), None) => {
+                        let value = vault.get_meta_field(name, key)?;
+                        Ok(Message::Meta(vec![(key.to_owned(), value)]))
+                    }
+                    (None, _) => Ok(Message::Meta(vault.get_meta(name)?)),
+                }
+            }
+            Command::Search { query, regex } => {
+                let hits = self.vaults.ref_current()?.search(query, *regex)?;
+                Ok(Message::SearchResults(hits))
+            }
+            Command::Index { action } => match action {
+                IndexAction::Rebuild => {
+                    let count = self.vaults.ref_current()?.rebuild_index()?;
+                    Ok(Message::IndexRebuilt(count))
+                }
+            },
+            Command::Trash {
+                action,
+                id,
+                older_than,
+            } => match action {
+                TrashAction::List => Ok(Message::Trash(self.vaults.list_trash()?)),
+                TrashAction::Restore => {
+                    let entry = self.vaults.restore_from_trash(id.as_ref().unwrap())?;
+                    Ok(Message::ItemRestored(
+                        entry.get_item().to_owned(),
+                        entry.get_name(),
+                    ))
+                }
+                TrashAction::Empty => {
+                    let older_than = older_than.as_deref().map(parse_age).transpose()?;
+                    Ok(Message::TrashEmptied(self.vaults.empty_trash(older_than)?))
+                }
+            },
+            Command::Undo => {
+                let entry = self.vaults.undo(&mut self.config)?;
+                Ok(Message::Undone(entry))
+            }
+            Command::Log { count } => Ok(Message::Log(self.vaults.log(*count)?)),
+            Command::Doctor { fix } => {
+                let interactive = self.interactive;
+                let confirm = |problem: &Problem| *fix || (interactive && confirm_fix(problem));
+
+                Ok(Message::Doctor(self.vaults.doctor(&confirm)))
             }
             Command::Config { config_type, value } => {
                 if config_type.is_none() {
@@ -138,8 +348,26 @@ impl App {
 
                 let config_type = config_type.as_ref().unwrap();
 
+                if config_type.is_vault_config() {
+                    let vault = self.vaults.mut_current()?;
+
+                    return if let Some(value) = value {
+                        vault.set_config(config_type, value)?;
+                        Ok(Message::ConfigSet(config_type.to_owned(), value.to_owned()))
+                    } else {
+                        let value = vault.get_config(config_type);
+                        Ok(Message::Config(config_type.to_owned(), value))
+                    };
+                }
+
                 if let Some(value) = value {
-                    self.config.set_config(config_type, value);
+                    let old_value = self.config.get_config(config_type);
+                    self.config.set_config(config_type, value)?;
+                    self.vaults.record(Operation::ConfigSet {
+                        key: config_type.to_owned(),
+                        value: old_value,
+                        new_value: value.to_owned(),
+                    });
                     Ok(Message::ConfigSet(config_type.to_owned(), value.to_owned()))
                 } else {
                     let value = self.config.get_config(config_type);
@@ -149,4 +377,18 @@ impl App {
             _ => Ok(Message::Empty),
         }
     }
+
+    fn open_daily(&mut self, date: &NaiveDate) -> Result<Message, Error> {
+        let created = self.vaults.mut_current()?.open_daily(
+            date,
+            &self.config.get_editor(),
+            &self.config.get_templates_dir(),
+            self.config.get_default_template(),
+        )?;
+
+        match created {
+            Some(name) => Ok(Message::ItemCreated(Item::Nt, name)),
+            None => Ok(Message::Empty),
+        }
+    }
 }
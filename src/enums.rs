@@ -1,12 +1,17 @@
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Item {
     Vault,
+    #[serde(rename(serialize = "vault"))]
     Vl,
     Note,
+    #[serde(rename(serialize = "note"))]
     Nt,
     Folder,
+    #[serde(rename(serialize = "folder"))]
     Fd,
 }
 
@@ -56,10 +61,19 @@ impl VaultItem {
     }
 }
 
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConfigType {
     Editor,
     Conflict,
+    DefaultTemplate,
+    DailyFolder,
+    DailyFormat,
+    DailyTemplate,
+    Extensions,
+    DefaultExtension,
+    VaultExtensions,
+    VaultDefaultExtension,
 }
 
 impl ConfigType {
@@ -67,6 +81,44 @@ impl ConfigType {
         match self {
             ConfigType::Editor => "editor",
             ConfigType::Conflict => "conflict",
+            ConfigType::DefaultTemplate => "default_template",
+            ConfigType::DailyFolder => "daily_folder",
+            ConfigType::DailyFormat => "daily_format",
+            ConfigType::DailyTemplate => "daily_template",
+            ConfigType::Extensions => "extensions",
+            ConfigType::DefaultExtension => "default_extension",
+            ConfigType::VaultExtensions => "vault_extensions",
+            ConfigType::VaultDefaultExtension => "vault_default_extension",
         }
     }
+
+    // @desc: Checks if the config is stored per vault (in the current vault's data file) rather
+    //        than in the app config.
+    pub fn is_vault_config(&self) -> bool {
+        matches!(
+            self,
+            ConfigType::DailyFolder
+                | ConfigType::DailyFormat
+                | ConfigType::DailyTemplate
+                | ConfigType::VaultExtensions
+                | ConfigType::VaultDefaultExtension
+        )
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum IndexAction {
+    Rebuild,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CheckType {
+    Links,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum TrashAction {
+    List,
+    Restore,
+    Empty,
 }
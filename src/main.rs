@@ -1,5 +1,6 @@
 mod app;
 mod enums;
+mod notes;
 mod output;
 mod state;
 mod traits;
@@ -8,16 +9,29 @@ mod utils;
 use crate::{
     app::App,
     output::{Message, Output},
+    state::args::Args,
 };
+use clap::Parser;
 
 fn main() {
-    let mut app = App::new();
+    let args = Args::parse();
+    let json = args.json;
 
-    match app.handle_args() {
-        Ok(msg) => match msg {
-            Message::Empty => (),
-            _ => println!("{}", Output::Message(msg)),
-        },
-        Err(err) => println!("{}", Output::Error(err)),
+    match App::new(args).and_then(|mut app| app.handle_args()) {
+        Ok(msg) if json => println!("{}", Output::Message(msg).to_json()),
+        Ok(Message::Empty) => (),
+        Ok(msg) => println!("{}", Output::Message(msg)),
+        Err(err) => {
+            let code = err.exit_code();
+            let output = Output::Error(err);
+
+            if json {
+                eprintln!("{}", output.to_json());
+            } else {
+                eprintln!("{output}");
+            }
+
+            std::process::exit(code);
+        }
     }
 }
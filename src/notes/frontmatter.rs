@@ -0,0 +1,149 @@
+use serde_yaml::{Mapping, Value};
+
+// @desc: YAML frontmatter of a note.
+//
+// @notes:
+//      -> Fields are kept in a mapping instead of a typed struct so that keys jot doesn't know
+//         about, and the order they were written in, survive a round trip.
+#[derive(Debug, Default)]
+pub struct Frontmatter {
+    fields: Mapping,
+}
+
+impl Frontmatter {
+    pub fn parse(yaml: &str) -> Option<Self> {
+        if yaml.trim().is_empty() {
+            return Some(Frontmatter::default());
+        }
+
+        match serde_yaml::from_str::<Value>(yaml) {
+            Ok(Value::Mapping(fields)) => Some(Frontmatter { fields }),
+            Ok(Value::Null) => Some(Frontmatter::default()),
+            _ => None,
+        }
+    }
+
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(&self.fields).unwrap()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.list("tags")
+    }
+
+    pub fn aliases(&self) -> Vec<String> {
+        self.list("aliases")
+    }
+
+    // @desc: Returns every field with its value formatted for display.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        self.fields
+            .iter()
+            .map(|(key, value)| (display_value(key), display_value(value)))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.fields.get(key).map(display_value)
+    }
+
+    // @desc: Sets a field from a string passed on the command line.
+    //
+    // @notes:
+    //      -> List fields (tags and aliases) are split on commas.
+    //      -> Other values are read as YAML scalars so that numbers and booleans keep their type,
+    //         anything else is stored as a string.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let value = match key {
+            "tags" | "aliases" => Value::Sequence(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_owned()))
+                    .collect(),
+            ),
+            _ => match serde_yaml::from_str::<Value>(value) {
+                Ok(value @ (Value::Bool(_) | Value::Number(_))) => value,
+                _ => Value::String(value.to_owned()),
+            },
+        };
+
+        self.fields.insert(Value::String(key.to_owned()), value);
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.fields.remove(key).is_some()
+    }
+
+    // @desc: Rewrites every string in the frontmatter, keys and nested values included.
+    pub fn map_strings(&mut self, change: &dyn Fn(&str) -> String) {
+        let fields = std::mem::take(&mut self.fields);
+        self.fields = map_mapping(fields, change);
+    }
+
+    // Obsidian accepts both a list and a comma separated string for list fields.
+    fn list(&self, key: &str) -> Vec<String> {
+        match self.fields.get(key) {
+            Some(Value::Sequence(items)) => items.iter().filter_map(scalar_to_string).collect(),
+            Some(value) => scalar_to_string(value)
+                .map(|items| {
+                    items
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => vec![],
+        }
+    }
+}
+
+fn map_mapping(mapping: Mapping, change: &dyn Fn(&str) -> String) -> Mapping {
+    mapping
+        .into_iter()
+        .map(|(key, value)| (map_value(key, change), map_value(value, change)))
+        .collect()
+}
+
+fn map_value(value: Value, change: &dyn Fn(&str) -> String) -> Value {
+    match value {
+        Value::String(value) => Value::String(change(&value)),
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .into_iter()
+                .map(|item| map_value(item, change))
+                .collect(),
+        ),
+        Value::Mapping(mapping) => Value::Mapping(map_mapping(mapping, change)),
+        value => value,
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.to_owned()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "".to_string(),
+        Value::Sequence(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<String>>()
+            .join(", "),
+        _ => scalar_to_string(value)
+            .unwrap_or_else(|| serde_yaml::to_string(value).unwrap().trim().to_string()),
+    }
+}
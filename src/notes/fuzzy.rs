@@ -0,0 +1,162 @@
+use crate::notes::note::Note;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+// Most candidates offered to pick from when several notes match.
+pub const MAX_CANDIDATES: usize = 10;
+
+// Picks one of the candidates (by index) when several notes match a query, e.g. by asking the
+// user. None leaves the query ambiguous.
+pub type Pick<'a> = &'a dyn Fn(&str, &[NoteMatch]) -> Option<usize>;
+
+// Bonuses for matched characters that follow the previous match, and that start a word.
+const CONSECUTIVE_BONUS: i64 = 5;
+const WORD_START_BONUS: i64 = 8;
+// Bonuses for the query appearing as a whole in the target, and being the whole target.
+const SUBSTRING_BONUS: i64 = 20;
+const EXACT_BONUS: i64 = 50;
+
+// @desc: A note matched by a fuzzy query, along with the text it matched on (its name, path,
+//        title or one of its aliases).
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteMatch {
+    path: PathBuf,
+    matched: String,
+    exact: bool,
+    #[serde(skip)]
+    score: i64,
+}
+
+impl NoteMatch {
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+}
+
+impl Display for NoteMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.with_extension("");
+        write!(f, "\x1b[0;34m{}\x1b[0m", path.display())?;
+
+        if path.file_name().unwrap_or_default().to_string_lossy() != self.matched
+            && path.to_string_lossy() != self.matched
+        {
+            write!(f, " ({})", self.matched)?;
+        }
+
+        Ok(())
+    }
+}
+
+// @desc: Scores how well a query matches a target, case insensitively. Returns None if the
+//        characters of the query don't all appear in the target, in order.
+//
+// @notes:
+//      -> Characters are matched greedily, so the score is a good guess rather than the best
+//         possible one. Whole matches get extra bonuses to make up for it.
+//      -> Shorter targets score higher, since they're closer to the query.
+pub fn fuzzy_score(query: &str, target: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    let target = target.to_lowercase();
+    let target_chars: Vec<char> = target.chars().collect();
+
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for char in query.chars() {
+        let index = (position..target_chars.len()).find(|index| target_chars[*index] == char)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if index == 0 || is_separator(target_chars[index - 1]) {
+            score += WORD_START_BONUS;
+        }
+
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    if target == query {
+        score += EXACT_BONUS;
+    } else if target.contains(&query) {
+        score += SUBSTRING_BONUS;
+    }
+
+    Some(score - (target_chars.len() as i64) / 4)
+}
+
+// @desc: Finds the notes matching a query, best match first.
+//
+// @notes:
+//      -> Notes are matched on their name, and on their title and aliases from frontmatter.
+//         Queries containing "/" are matched on paths (relative to the vault) too.
+//      -> "notes" are absolute paths, while paths of matches are relative to the vault.
+pub fn find_notes(query: &str, notes: Vec<PathBuf>, vault_path: &Path) -> Vec<NoteMatch> {
+    let mut matches: Vec<NoteMatch> = notes
+        .iter()
+        .filter_map(|note| {
+            let path = note.strip_prefix(vault_path).unwrap().to_path_buf();
+
+            let mut targets = vec![path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()];
+            if query.contains('/') {
+                targets.push(path.with_extension("").to_string_lossy().to_string());
+            }
+            targets.append(&mut frontmatter_names(note));
+
+            targets
+                .into_iter()
+                .filter_map(|target| Some((fuzzy_score(query, &target)?, target)))
+                .max_by_key(|(score, _)| *score)
+                .map(|(score, matched)| NoteMatch {
+                    exact: matched.to_lowercase() == query.to_lowercase(),
+                    path,
+                    matched,
+                    score,
+                })
+        })
+        .collect();
+
+    matches.sort_by_key(|note_match| (Reverse(note_match.score), note_match.path.to_owned()));
+    matches
+}
+
+// @desc: Collects the title and aliases of a note. Notes that can't be read or parsed have none.
+//
+// @notes:
+//      -> Only the frontmatter is read, since every note in the vault goes through here.
+fn frontmatter_names(path: &Path) -> Vec<String> {
+    let frontmatter = match Note::read_frontmatter(path) {
+        Some(frontmatter) => frontmatter,
+        None => return vec![],
+    };
+
+    let mut names = frontmatter.aliases();
+    if let Some(title) = frontmatter.get("title") {
+        names.push(title);
+    }
+
+    names
+}
+
+fn is_separator(char: char) -> bool {
+    matches!(char, '/' | '-' | '_' | ' ' | '.')
+}
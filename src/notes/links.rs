@@ -0,0 +1,723 @@
+use crate::{
+    notes::types::NoteTypes,
+    output::{error::Error, warn},
+    utils::walk_files,
+};
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs::{read_to_string, write},
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    sync::LazyLock,
+};
+
+static WIKI_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\[\]|#]*)(#[^\[\]|]*)?(\|[^\[\]]*)?\]\]").unwrap());
+static MARKDOWN_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\[\]]*)\]\(([^()\s]+)\)").unwrap());
+
+#[derive(Debug, PartialEq)]
+enum LinkKind {
+    // [[target#anchor|label]]
+    Wiki,
+    // [label](target#anchor)
+    Markdown,
+}
+
+// @desc: A link found in a note.
+//
+// @notes:
+//      -> "range" is the byte range of the target as written in the note's content, which is
+//         what gets replaced when links are rewritten.
+//      -> "encoded" is set for markdown links that had their spaces written as "%20".
+#[derive(Debug)]
+struct Link {
+    kind: LinkKind,
+    target: String,
+    line: usize,
+    range: Range<usize>,
+    encoded: bool,
+}
+
+// @desc: A line in a note that links to another note.
+#[derive(Debug, Serialize)]
+pub struct Backlink {
+    path: PathBuf,
+    line: usize,
+    context: String,
+}
+
+impl Display for Backlink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\x1b[0;34m{}\x1b[0m:{}: {}",
+            self.path.display(),
+            self.line,
+            self.context
+        )
+    }
+}
+
+// @desc: Outcome of rewriting links after notes were moved (or renamed).
+//
+// @notes:
+//      -> "failed" holds the notes that couldn't be read or written. They're skipped rather than
+//         failing the rewrite, since the notes were already moved by then.
+#[derive(Debug, Default)]
+pub struct LinkUpdate {
+    updated: usize,
+    failed: Vec<PathBuf>,
+}
+
+impl LinkUpdate {
+    pub fn get_updated(&self) -> usize {
+        self.updated
+    }
+
+    pub fn get_failed(&self) -> &Vec<PathBuf> {
+        &self.failed
+    }
+}
+
+// @desc: A link that doesn't resolve to any note.
+#[derive(Debug, Serialize)]
+pub struct BrokenLink {
+    path: PathBuf,
+    line: usize,
+    target: String,
+}
+
+impl Display for BrokenLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\x1b[0;34m{}\x1b[0m:{}: {}",
+            self.path.display(),
+            self.line,
+            self.target
+        )
+    }
+}
+
+// @desc: Result of checking the links of a vault.
+//
+// @notes:
+//      -> Orphans are notes that no other note links to.
+//      -> Unreadable notes (e.g. ones that aren't valid UTF-8) can't be checked, so they're
+//         reported as problems too.
+#[derive(Debug, Serialize)]
+pub struct LinkReport {
+    broken: Vec<BrokenLink>,
+    orphans: Vec<PathBuf>,
+    unreadable: Vec<PathBuf>,
+}
+
+impl LinkReport {
+    pub fn is_clean(&self) -> bool {
+        self.broken.is_empty() && self.orphans.is_empty() && self.unreadable.is_empty()
+    }
+}
+
+impl Display for LinkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = vec![if self.unreadable.is_empty() {
+            format!(
+                "found {} broken link(s) and {} orphan note(s)",
+                self.broken.len(),
+                self.orphans.len()
+            )
+        } else {
+            format!(
+                "found {} broken link(s), {} orphan note(s) and {} unreadable note(s)",
+                self.broken.len(),
+                self.orphans.len(),
+                self.unreadable.len()
+            )
+        }];
+
+        if !self.broken.is_empty() {
+            lines.push("broken links:".to_string());
+            lines.extend(self.broken.iter().map(|link| format!("    {link}")));
+        }
+
+        if !self.orphans.is_empty() {
+            lines.push("orphan notes:".to_string());
+            lines.extend(
+                self.orphans
+                    .iter()
+                    .map(|orphan| format!("    \x1b[0;34m{}\x1b[0m", orphan.display())),
+            );
+        }
+
+        if !self.unreadable.is_empty() {
+            lines.push("unreadable notes:".to_string());
+            lines.extend(
+                self.unreadable
+                    .iter()
+                    .map(|note| format!("    \x1b[0;34m{}\x1b[0m", note.display())),
+            );
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// @desc: Resolves links against the notes of a vault.
+//
+// @notes:
+//      -> All paths are relative to the root of the vault.
+//      -> "attachments" are the files that aren't notes (e.g. images), which wikilinks can embed.
+pub struct LinkResolver {
+    root: PathBuf,
+    notes: Vec<PathBuf>,
+    attachments: Vec<PathBuf>,
+    note_types: NoteTypes,
+}
+
+impl LinkResolver {
+    pub fn new(root: &Path, note_types: NoteTypes) -> Result<Self, Error> {
+        let (notes, attachments) = walk_files(root)?
+            .into_iter()
+            .map(|file| file.strip_prefix(root).unwrap().to_path_buf())
+            .partition(|file| note_types.is_note(file));
+
+        Ok(LinkResolver {
+            root: root.to_path_buf(),
+            notes,
+            attachments,
+            note_types,
+        })
+    }
+
+    // @desc: Finds the note a link in source points to.
+    //
+    // @notes:
+    //      -> Markdown links are paths relative to the folder of source (or to the root of the
+    //         vault if they start with "/").
+    //      -> Wikilinks are tried as a path from the root of the vault, then as a path from the
+    //         folder of source, and finally by note name. When several notes share the name, the
+    //         one in the same folder as source wins, otherwise the one with the shortest path.
+    //      -> Wikilinks without an extension point to notes with any of the note extensions,
+    //         which are tried in order.
+    //      -> Wikilinks with another extension (like "![[image.png]]") that don't point to a note
+    //         are resolved the same way against attachments. Names with dots (like dates) are
+    //         still tried as notes first.
+    fn resolve(&self, source: &Path, link: &Link) -> Option<PathBuf> {
+        let folder = source.parent().unwrap_or(Path::new(""));
+
+        match link.kind {
+            LinkKind::Markdown => {
+                let path = if let Some(path) = link.target.strip_prefix('/') {
+                    PathBuf::from(path)
+                } else {
+                    folder.join(&link.target)
+                };
+
+                normalize_path(&path).filter(|path| self.notes.contains(path))
+            }
+            LinkKind::Wiki => {
+                let target = PathBuf::from(link.target.trim_start_matches('/'));
+                // Targets without a file name (like "[[]]") don't point to any note.
+                target.file_name()?;
+                let targets = self.note_types.candidates(&target);
+
+                if let Some(note) = find_target(&self.notes, folder, &targets) {
+                    return Some(note);
+                }
+
+                if target.extension().is_some() && !self.note_types.is_note(&target) {
+                    return find_target(&self.attachments, folder, &[target]);
+                }
+
+                None
+            }
+        }
+    }
+
+    // @desc: Maps the notes at or inside path to where they end up once path is moved to
+    //        new_path.
+    pub fn moves(&self, path: &Path, new_path: &Path) -> BTreeMap<PathBuf, PathBuf> {
+        self.notes
+            .iter()
+            .filter_map(|note| {
+                let rest = note.strip_prefix(path).ok()?;
+                let new_note = if rest.as_os_str().is_empty() {
+                    new_path.to_path_buf()
+                } else {
+                    new_path.join(rest)
+                };
+
+                Some((note.to_owned(), new_note))
+            })
+            .collect()
+    }
+
+    // @desc: Rewrites links broken by notes having been moved (or renamed), and returns the number
+    //        of notes that were changed along with the ones that failed.
+    //
+    // @notes:
+    //      -> Has to be called after the notes were moved, on a resolver created before that, so
+    //         that links still resolve to the old paths.
+    //      -> Markdown links are rewritten when either the note they point to or the note they
+    //         are in has moved, since they're relative.
+    //      -> Wikilinks written as a bare name keep working after a move, so only a change of name
+    //         rewrites them. Wikilinks written as a path get the new path from the vault's root.
+    pub fn rewrite_links(&self, moves: &BTreeMap<PathBuf, PathBuf>) -> LinkUpdate {
+        let mut update = LinkUpdate::default();
+
+        for note in &self.notes {
+            let new_note = moves.get(note).unwrap_or(note);
+            let path = self.root.join(new_note);
+
+            let mut content = match read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => {
+                    update.failed.push(new_note.to_owned());
+                    continue;
+                }
+            };
+            let mut replacements = vec![];
+
+            for link in parse_links(&content, &self.note_types) {
+                let target = match self.resolve(note, &link) {
+                    Some(target) => target,
+                    None => continue,
+                };
+                let new_target = moves.get(&target).unwrap_or(&target);
+
+                let new_text = match link.kind {
+                    LinkKind::Markdown if new_note != note || new_target != &target => {
+                        let text = relative_path(new_note.parent().unwrap(), new_target);
+                        if link.encoded {
+                            text.replace(' ', "%20")
+                        } else {
+                            text
+                        }
+                    }
+                    LinkKind::Wiki if new_target != &target => {
+                        let mut new_target = new_target.to_owned();
+                        if !self.note_types.is_note(Path::new(&link.target)) {
+                            new_target.set_extension("");
+                        }
+
+                        if link.target.contains('/') {
+                            relative_path(Path::new(""), &new_target)
+                        } else {
+                            new_target
+                                .file_name()
+                                .unwrap()
+                                .to_string_lossy()
+                                .to_string()
+                        }
+                    }
+                    _ => continue,
+                };
+
+                if content[link.range.clone()] != new_text {
+                    replacements.push((link.range, new_text));
+                }
+            }
+
+            if replacements.is_empty() {
+                continue;
+            }
+
+            for (range, new_text) in replacements.into_iter().rev() {
+                content.replace_range(range, &new_text);
+            }
+
+            if write(&path, content).is_ok() {
+                update.updated += 1;
+            } else {
+                update.failed.push(new_note.to_owned());
+            }
+        }
+
+        update
+    }
+
+    // @desc: Collects links that don't resolve, notes that nothing links to and notes that
+    //        can't be read.
+    pub fn check(&self) -> LinkReport {
+        let mut broken = vec![];
+        let mut linked: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut unreadable = vec![];
+
+        for note in &self.notes {
+            let content = match read_to_string(self.root.join(note)) {
+                Ok(content) => content,
+                Err(_) => {
+                    unreadable.push(note.to_owned());
+                    continue;
+                }
+            };
+
+            for link in parse_links(&content, &self.note_types) {
+                match self.resolve(note, &link) {
+                    Some(target) => {
+                        if &target != note {
+                            linked.insert(target);
+                        }
+                    }
+                    None => broken.push(BrokenLink {
+                        path: note.to_owned(),
+                        line: link.line,
+                        target: content[link.range].to_owned(),
+                    }),
+                }
+            }
+        }
+
+        let orphans = self
+            .notes
+            .iter()
+            .filter(|note| !linked.contains(*note))
+            .cloned()
+            .collect();
+
+        LinkReport {
+            broken,
+            orphans,
+            unreadable,
+        }
+    }
+
+    // @desc: Collects every line in the vault that links to target.
+    //
+    // @notes:
+    //      -> Notes that can't be read are skipped with a warning.
+    pub fn backlinks(&self, target: &Path) -> Vec<Backlink> {
+        let mut backlinks = vec![];
+
+        for note in &self.notes {
+            let content = match read_to_string(self.root.join(note)) {
+                Ok(content) => content,
+                Err(error) => {
+                    warn(&format!("skipped {}: {error}", note.display()));
+                    continue;
+                }
+            };
+            let lines: Vec<&str> = content.lines().collect();
+
+            for link in parse_links(&content, &self.note_types) {
+                if note != target && self.resolve(note, &link).as_deref() == Some(target) {
+                    if backlinks
+                        .last()
+                        .is_some_and(|last: &Backlink| &last.path == note && last.line == link.line)
+                    {
+                        continue;
+                    }
+
+                    backlinks.push(Backlink {
+                        path: note.to_owned(),
+                        line: link.line,
+                        context: lines[link.line - 1].trim().to_owned(),
+                    });
+                }
+            }
+        }
+
+        backlinks
+    }
+}
+
+// @desc: Finds wikilinks and relative markdown links to notes in content, ignoring fenced code
+//        blocks.
+//
+// @notes:
+//      -> "#heading" anchors and "|alias" labels are not part of the target.
+//      -> Markdown links with a scheme (like "https://") or to files other than notes are skipped.
+fn parse_links(content: &str, note_types: &NoteTypes) -> Vec<Link> {
+    let mut links = vec![];
+    let mut in_code_block = false;
+    let mut offset = 0;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            continue;
+        }
+
+        for captures in WIKI_PATTERN.captures_iter(line) {
+            let group = captures.get(1).unwrap();
+            let target = group.as_str().trim();
+            if target.is_empty() {
+                continue;
+            }
+
+            let start = line_offset + group.start() + group.as_str().find(target).unwrap();
+            links.push(Link {
+                kind: LinkKind::Wiki,
+                target: target.to_owned(),
+                line: index + 1,
+                range: start..start + target.len(),
+                encoded: false,
+            });
+        }
+
+        for captures in MARKDOWN_PATTERN.captures_iter(line) {
+            let group = captures.get(2).unwrap();
+            let target = group.as_str().split('#').next().unwrap();
+            if target.contains(':') || !note_types.is_note(Path::new(target)) {
+                continue;
+            }
+
+            let start = line_offset + group.start();
+            links.push(Link {
+                kind: LinkKind::Markdown,
+                target: target.replace("%20", " "),
+                line: index + 1,
+                range: start..start + target.len(),
+                encoded: target.contains("%20"),
+            });
+        }
+    }
+
+    links.sort_by_key(|link| link.range.start);
+    links
+}
+
+// @desc: Finds the file a wikilink in folder points to, out of files, trying each of the targets
+//        in order. See LinkResolver::resolve for how targets are matched.
+fn find_target(files: &[PathBuf], folder: &Path, targets: &[PathBuf]) -> Option<PathBuf> {
+    for target in targets {
+        for candidate in [target.to_owned(), folder.join(target)] {
+            if let Some(candidate) = normalize_path(&candidate) {
+                if let Some(file) = find_file(files, &candidate) {
+                    return Some(file);
+                }
+            }
+        }
+    }
+
+    targets.iter().find_map(|target| {
+        let file_name = target.file_name()?.to_string_lossy().to_lowercase();
+        let mut matches: Vec<&PathBuf> = files
+            .iter()
+            .filter(|file| file.file_name().unwrap().to_string_lossy().to_lowercase() == file_name)
+            .collect();
+
+        matches.sort_by_key(|file| (file.parent() != Some(folder), file.components().count()));
+        matches.first().map(|file| file.to_path_buf())
+    })
+}
+
+// Obsidian treats links case-insensitively.
+fn find_file(files: &[PathBuf], path: &Path) -> Option<PathBuf> {
+    let path = path.to_string_lossy().to_lowercase();
+
+    files
+        .iter()
+        .find(|file| file.to_string_lossy().to_lowercase() == path)
+        .cloned()
+}
+
+// @desc: Generates the relative path from a folder to a note, as used in markdown links.
+fn relative_path(folder: &Path, path: &Path) -> String {
+    let folder: Vec<Component> = folder.components().collect();
+    let path: Vec<Component> = path.components().collect();
+
+    let common = folder
+        .iter()
+        .zip(path.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); folder.len() - common];
+    parts.extend(
+        path[common..]
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy().to_string()),
+    );
+
+    parts.join("/")
+}
+
+// @desc: Collapses "." and ".." in a relative path without touching the fs. Returns None if the
+//        path climbs out of the vault.
+fn normalize_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::ParentDir if !normalized.pop() => return None,
+            _ => (),
+        }
+    }
+
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env::temp_dir,
+        fs::{self, create_dir_all},
+    };
+
+    fn create_vault(name: &str, notes: &[(&str, &str)]) -> PathBuf {
+        let root = temp_dir().join(format!("jot links test {name} {}", std::process::id()));
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        for (path, content) in notes {
+            let path = root.join(path);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        root
+    }
+
+    // Moves path to new_path the way renames, moves and their undos do, then rewrites links.
+    fn move_and_rewrite(root: &Path, path: &str, new_path: &str) -> LinkUpdate {
+        let resolver = LinkResolver::new(root, NoteTypes::default()).unwrap();
+
+        create_dir_all(root.join(new_path).parent().unwrap()).unwrap();
+        fs::rename(root.join(path), root.join(new_path)).unwrap();
+
+        resolver.rewrite_links(&resolver.moves(Path::new(path), Path::new(new_path)))
+    }
+
+    fn read(root: &Path, path: &str) -> String {
+        fs::read_to_string(root.join(path)).unwrap()
+    }
+
+    #[test]
+    fn parse_links_leaves_headings_and_aliases_out_of_targets() {
+        let content = "[[alpha#Plan|the plan]] and [b](../beta%20notes.md#top)\n";
+        let links = parse_links(content, &NoteTypes::default());
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].kind, LinkKind::Wiki);
+        assert_eq!(links[0].target, "alpha");
+        assert_eq!(&content[links[0].range.clone()], "alpha");
+        assert_eq!(links[1].kind, LinkKind::Markdown);
+        assert_eq!(links[1].target, "../beta notes.md");
+        assert_eq!(&content[links[1].range.clone()], "../beta%20notes.md");
+        assert!(links[1].encoded);
+    }
+
+    #[test]
+    fn parse_links_skips_code_blocks_and_external_links() {
+        let content = "```\n[[alpha]]\n```\n[site](https://example.com) [[beta]]\n";
+        let links = parse_links(content, &NoteTypes::default());
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "beta");
+        assert_eq!(links[0].line, 4);
+    }
+
+    #[test]
+    fn rename_rewrites_links_and_keeps_headings_and_aliases() {
+        let root = create_vault(
+            "rename",
+            &[
+                ("alpha.md", "# Plan\n"),
+                (
+                    "notes/index.md",
+                    "[[alpha]], [[alpha#Plan|the plan]] and [a](../alpha.md#plan)\n",
+                ),
+            ],
+        );
+
+        let update = move_and_rewrite(&root, "alpha.md", "omega.md");
+        let index = read(&root, "notes/index.md");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(update.get_updated(), 1);
+        assert!(update.get_failed().is_empty());
+        assert_eq!(
+            index,
+            "[[omega]], [[omega#Plan|the plan]] and [a](../omega.md#plan)\n"
+        );
+    }
+
+    #[test]
+    fn move_rewrites_relative_links_in_and_to_the_note() {
+        let root = create_vault(
+            "move",
+            &[
+                ("alpha.md", "[b](beta.md) and [[beta]]\n"),
+                ("beta.md", "[a](alpha.md), [[alpha]] and [[./alpha]]\n"),
+            ],
+        );
+
+        move_and_rewrite(&root, "alpha.md", "archive/alpha.md");
+        let (alpha, beta) = (read(&root, "archive/alpha.md"), read(&root, "beta.md"));
+        fs::remove_dir_all(&root).unwrap();
+
+        // Bare wikilinks resolve by name, so only paths change.
+        assert_eq!(alpha, "[b](../beta.md) and [[beta]]\n");
+        assert_eq!(
+            beta,
+            "[a](archive/alpha.md), [[alpha]] and [[archive/alpha]]\n"
+        );
+    }
+
+    #[test]
+    fn folder_move_recomputes_relative_depth() {
+        let root = create_vault(
+            "folder",
+            &[
+                (
+                    "readme.md",
+                    "[p](projects/alpha/plan.md) [[projects/alpha/plan]]\n",
+                ),
+                (
+                    "projects/alpha/plan.md",
+                    "[r](../../readme.md) [t](tasks/todo.md)\n",
+                ),
+                ("projects/alpha/tasks/todo.md", "[p](../plan.md)\n"),
+            ],
+        );
+
+        let update = move_and_rewrite(&root, "projects/alpha", "alpha");
+        let readme = read(&root, "readme.md");
+        let plan = read(&root, "alpha/plan.md");
+        let todo = read(&root, "alpha/tasks/todo.md");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(update.get_updated(), 2);
+        assert_eq!(readme, "[p](alpha/plan.md) [[alpha/plan]]\n");
+        assert_eq!(plan, "[r](../readme.md) [t](tasks/todo.md)\n");
+        // Links between notes that moved together don't change.
+        assert_eq!(todo, "[p](../plan.md)\n");
+    }
+
+    #[test]
+    fn undo_restores_the_original_links() {
+        let notes = [
+            (
+                "readme.md",
+                "[p](projects/plan.md), [[plan|Plan]] and [[projects/plan#Goals]]\n",
+            ),
+            ("projects/plan.md", "[r](../readme.md) [[readme]]\n"),
+        ];
+        let root = create_vault("undo", &notes);
+
+        move_and_rewrite(&root, "projects/plan.md", "archive/2026/roadmap.md");
+        assert_ne!(read(&root, "readme.md"), notes[0].1);
+
+        move_and_rewrite(&root, "archive/2026/roadmap.md", "projects/plan.md");
+        let (readme, plan) = (read(&root, "readme.md"), read(&root, "projects/plan.md"));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(readme, notes[0].1);
+        assert_eq!(plan, notes[1].1);
+    }
+}
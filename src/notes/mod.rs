@@ -0,0 +1,8 @@
+pub mod frontmatter;
+pub mod fuzzy;
+pub mod links;
+pub mod note;
+pub mod search;
+pub mod tags;
+pub mod template;
+pub mod types;
@@ -0,0 +1,144 @@
+use crate::{notes::frontmatter::Frontmatter, output::error::Error};
+use chrono::Local;
+use std::{
+    fs::{read_to_string, write, File},
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+// Format used for "created" and "modified" timestamps in frontmatter.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+// @desc: Contents of a note split into its frontmatter and body.
+#[derive(Debug, Default)]
+pub struct Note {
+    frontmatter: Frontmatter,
+    body: String,
+}
+
+impl Note {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(error) => return Err(Error::Undefined(error)),
+        };
+
+        match Note::parse(&content) {
+            Some(note) => Ok(note),
+            None => Err(Error::InvalidFrontmatter(path.to_path_buf())),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        if let Err(error) = write(path, self.to_string()) {
+            return Err(Error::Undefined(error));
+        }
+
+        Ok(())
+    }
+
+    // @desc: Splits content into frontmatter and body. Returns None if the frontmatter isn't
+    //        valid YAML.
+    //
+    // @notes:
+    //      -> Frontmatter has to start on the very first line with "---" and end with a line
+    //         containing "---" (or "..."). Anything else is treated as body.
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.split_inclusive('\n');
+
+        if lines.next().map(str::trim_end) != Some("---") {
+            return Some(Note {
+                frontmatter: Frontmatter::default(),
+                body: content.to_owned(),
+            });
+        }
+
+        let mut yaml = String::new();
+        let mut consumed = content.find('\n').unwrap() + 1;
+
+        for line in lines {
+            consumed += line.len();
+
+            if matches!(line.trim_end(), "---" | "...") {
+                return Some(Note {
+                    frontmatter: Frontmatter::parse(&yaml)?,
+                    body: content[consumed..].to_owned(),
+                });
+            }
+
+            yaml.push_str(line);
+        }
+
+        Some(Note {
+            frontmatter: Frontmatter::default(),
+            body: content.to_owned(),
+        })
+    }
+
+    // @desc: Reads only the frontmatter of a note, for when its body isn't needed. Returns None
+    //        if the note can't be read or its frontmatter isn't valid YAML.
+    //
+    // @notes:
+    //      -> Stops reading at the end of the frontmatter (or after the first line, for notes
+    //         without one), following the same rules as parse().
+    pub fn read_frontmatter(path: &Path) -> Option<Frontmatter> {
+        let mut lines = BufReader::new(File::open(path).ok()?).lines();
+
+        if lines.next()?.ok()?.trim_end() != "---" {
+            return Some(Frontmatter::default());
+        }
+
+        let mut yaml = String::new();
+
+        for line in lines {
+            let line = line.ok()?;
+
+            if matches!(line.trim_end(), "---" | "...") {
+                return Frontmatter::parse(&yaml);
+            }
+
+            yaml.push_str(&line);
+            yaml.push('\n');
+        }
+
+        Some(Frontmatter::default())
+    }
+
+    pub fn frontmatter(&self) -> &Frontmatter {
+        &self.frontmatter
+    }
+
+    pub fn frontmatter_mut(&mut self) -> &mut Frontmatter {
+        &mut self.frontmatter
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+    }
+
+    pub fn stamp_created(&mut self) {
+        self.frontmatter.set("created", &timestamp());
+    }
+
+    pub fn stamp_modified(&mut self) {
+        self.frontmatter.set("modified", &timestamp());
+    }
+}
+
+impl std::fmt::Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.frontmatter.is_empty() {
+            write!(f, "{}", self.body)
+        } else {
+            write!(f, "---\n{}---\n{}", self.frontmatter.to_yaml(), self.body)
+        }
+    }
+}
+
+fn timestamp() -> String {
+    Local::now().format(TIMESTAMP_FORMAT).to_string()
+}
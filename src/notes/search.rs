@@ -0,0 +1,141 @@
+use crate::output::{error::Error, warn};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+// Lines longer than this are cut down to a window around the first match.
+const SNIPPET_WIDTH: usize = 120;
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    path: PathBuf,
+    line: usize,
+    snippet: String,
+    ranges: Vec<(usize, usize)>,
+}
+
+impl Display for SearchHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut snippet = String::new();
+        let mut last = 0;
+
+        for (start, end) in &self.ranges {
+            snippet.push_str(&self.snippet[last..*start]);
+            snippet.push_str(&format!("\x1b[0;31m{}\x1b[0m", &self.snippet[*start..*end]));
+            last = *end;
+        }
+        snippet.push_str(&self.snippet[last..]);
+
+        write!(
+            f,
+            "\x1b[0;34m{}\x1b[0m:{}: {snippet}",
+            self.path.display(),
+            self.line
+        )
+    }
+}
+
+// @desc: Builds the regex used for searching.
+//
+// @notes:
+//      -> Plain queries are escaped and matched case-insensitively, regex queries are used as is.
+pub fn build_pattern(query: &str, is_regex: bool) -> Result<Regex, Error> {
+    let pattern = if is_regex {
+        query.to_owned()
+    } else {
+        regex::escape(query)
+    };
+
+    match RegexBuilder::new(&pattern)
+        .case_insensitive(!is_regex)
+        .build()
+    {
+        Ok(pattern) => Ok(pattern),
+        Err(_) => Err(Error::InvalidPattern(query.to_owned())),
+    }
+}
+
+// @desc: Matches the pattern against every given note and returns the matching lines with paths
+//        relative to root.
+//
+// @notes:
+//      -> Notes that can't be read (e.g. ones that aren't valid UTF-8) are skipped with a warning
+//         instead of failing the whole search.
+pub fn search_notes(pattern: &Regex, notes: Vec<PathBuf>, root: &Path) -> Vec<SearchHit> {
+    let mut hits = vec![];
+
+    for note in notes {
+        hits.append(&mut search_note(pattern, &note, root));
+    }
+
+    hits
+}
+
+fn search_note(pattern: &Regex, note: &Path, root: &Path) -> Vec<SearchHit> {
+    let relative_path = note.strip_prefix(root).unwrap_or(note).to_path_buf();
+    let content = match read_to_string(note) {
+        Ok(content) => content,
+        Err(error) => {
+            warn(&format!("skipped {}: {error}", relative_path.display()));
+            return vec![];
+        }
+    };
+
+    let hits = content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let first = pattern.find(line)?;
+            let (snippet, ranges) = generate_snippet(pattern, line, first.start());
+
+            Some(SearchHit {
+                path: relative_path.clone(),
+                line: index + 1,
+                snippet,
+                ranges,
+            })
+        })
+        .collect();
+
+    hits
+}
+
+// @desc: Trims a matched line and cuts it down to SNIPPET_WIDTH around the first match, then
+//        collects the byte ranges of all matches inside the snippet for highlighting.
+fn generate_snippet(
+    pattern: &Regex,
+    line: &str,
+    first_match: usize,
+) -> (String, Vec<(usize, usize)>) {
+    let mut start = first_match.saturating_sub(SNIPPET_WIDTH / 3);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+
+    let mut end = (start + SNIPPET_WIDTH).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let window = if start == 0 {
+        line[..end].trim_start()
+    } else {
+        &line[start..end]
+    };
+    let window = window.trim_end();
+
+    let prefix = if start > 0 { "..." } else { "" };
+    let suffix = if end < line.len() { "..." } else { "" };
+
+    let ranges = pattern
+        .find_iter(window)
+        .filter(|found| !found.as_str().is_empty())
+        .map(|found| (found.start() + prefix.len(), found.end() + prefix.len()))
+        .collect();
+
+    (format!("{prefix}{window}{suffix}"), ranges)
+}
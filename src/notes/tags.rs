@@ -0,0 +1,96 @@
+use crate::notes::note::Note;
+use regex::Regex;
+use std::{collections::BTreeSet, fs::read_to_string, path::Path, sync::LazyLock};
+
+static TAG_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)#([\p{L}\p{N}_\-/]+)").unwrap());
+
+// @desc: Collects the tags of a note, from the "tags" field of its frontmatter and from "#tags"
+//        in its body.
+//
+// @notes:
+//      -> Tags are lowercased and returned without the leading "#".
+//      -> Nested tags roll up, i.e. "#project/alpha" also yields "project".
+//      -> Notes that can't be read yield no tags, and notes with invalid frontmatter are scanned
+//         as plain text.
+pub fn note_tags(path: &Path) -> BTreeSet<String> {
+    let content = match read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return BTreeSet::new(),
+    };
+
+    let mut tags = vec![];
+
+    match Note::parse(&content) {
+        Some(note) => {
+            tags.append(&mut note.frontmatter().tags());
+            tags.append(&mut body_tags(note.body()));
+        }
+        None => tags.append(&mut body_tags(&content)),
+    }
+
+    tags.iter()
+        .map(|tag| normalize_tag(tag))
+        .filter(|tag| !tag.is_empty())
+        .flat_map(|tag| roll_up(&tag))
+        .collect()
+}
+
+// @desc: Checks if a note's tag matches the tag asked for, which is the case when they're the same
+//        or the note's tag is nested under it.
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    let filter = normalize_tag(filter);
+    tag == filter || tag.starts_with(&format!("{filter}/"))
+}
+
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .trim_matches('/')
+        .to_lowercase()
+}
+
+// @desc: Finds "#tags" in the body of a note, ignoring fenced code blocks.
+//
+// @notes:
+//      -> A tag has to start at the beginning of a line or after whitespace, so headings ("# ")
+//         and anchors inside links don't count.
+//      -> Purely numeric tags like "#1" aren't tags in Obsidian either.
+fn body_tags(body: &str) -> Vec<String> {
+    let mut in_code_block = false;
+    let mut tags = vec![];
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            continue;
+        }
+
+        for captures in TAG_PATTERN.captures_iter(line) {
+            let tag = &captures[1];
+
+            if !tag.chars().all(|char| char.is_numeric() || char == '/') {
+                tags.push(tag.to_owned());
+            }
+        }
+    }
+
+    tags
+}
+
+fn roll_up(tag: &str) -> Vec<String> {
+    let mut tags = vec![];
+
+    for (index, char) in tag.char_indices() {
+        if char == '/' {
+            tags.push(tag[..index].to_owned());
+        }
+    }
+    tags.push(tag.to_owned());
+
+    tags
+}
@@ -0,0 +1,73 @@
+use crate::{notes::note::Note, output::error::Error};
+use regex::{Captures, Regex};
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+static PLACEHOLDER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([\w\-]+)\s*\}\}").unwrap());
+static TOKEN_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"__jot_placeholder_(\d+)__").unwrap());
+
+// @desc: Finds a template by name, going through the folders in order. Templates are markdown
+//        files, so name is the file stem.
+//
+// @notes:
+//      -> Names can't point outside of the folders, so path separators and ".." are rejected.
+pub fn find_template(name: &str, folders: &[PathBuf]) -> Result<PathBuf, Error> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(Error::InvalidName);
+    }
+
+    folders
+        .iter()
+        .map(|folder| folder.join(format!("{name}.md")))
+        .find(|path| path.is_file())
+        .ok_or_else(|| Error::TemplateNotFound(name.to_owned()))
+}
+
+// @desc: Reads a template and expands its placeholders into a new note.
+//
+// @notes:
+//      -> Placeholders are written as "{{name}}" (spaces around the name are allowed).
+//         Placeholders without a value are left untouched.
+//      -> The template is parsed before values are filled in, so that values can't change the
+//         structure of its frontmatter. Placeholders are swapped for plain tokens first (since
+//         "{{name}}" isn't a valid YAML value on its own), and values replace the tokens in the
+//         parsed frontmatter as strings, and in the body as they are.
+pub fn render_template(path: &Path, vars: &BTreeMap<String, String>) -> Result<Note, Error> {
+    let template = match read_to_string(path) {
+        Ok(template) => template,
+        Err(error) => return Err(Error::Undefined(error)),
+    };
+
+    let mut placeholders: Vec<String> = vec![];
+    let content = PLACEHOLDER_PATTERN.replace_all(&template, |captures: &Captures| {
+        placeholders.push(captures[0].to_owned());
+        format!("__jot_placeholder_{}__", placeholders.len() - 1)
+    });
+
+    let mut note =
+        Note::parse(&content).ok_or_else(|| Error::InvalidFrontmatter(path.to_path_buf()))?;
+
+    let expand = |text: &str| -> String {
+        TOKEN_PATTERN
+            .replace_all(text, |captures: &Captures| {
+                let placeholder = &placeholders[captures[1].parse::<usize>().unwrap()];
+                let name = PLACEHOLDER_PATTERN.captures(placeholder).unwrap()[1].to_owned();
+                vars.get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| placeholder.to_owned())
+            })
+            .to_string()
+    };
+
+    note.frontmatter_mut().map_strings(&expand);
+    let body = expand(note.body());
+    note.set_body(body);
+
+    Ok(note)
+}
@@ -0,0 +1,222 @@
+use crate::output::error::Error;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// @desc: Note extensions as set in config, either globally or for a vault.
+//
+// @notes:
+//      -> Unset fields of a vault fall back to the global ones, and unset global ones to "md".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteTypesConfig {
+    extensions: Option<Vec<String>>,
+    default_extension: Option<String>,
+}
+
+impl NoteTypesConfig {
+    // An empty value unsets the extensions.
+    pub fn set_extensions(&mut self, extensions: &str) -> Result<(), Error> {
+        self.extensions = if extensions.is_empty() {
+            None
+        } else {
+            Some(parse_extensions(extensions)?)
+        };
+        Ok(())
+    }
+
+    pub fn get_extensions(&self) -> String {
+        self.extensions
+            .as_ref()
+            .map(|extensions| extensions.join(","))
+            .unwrap_or_default()
+    }
+
+    // An empty value unsets the default extension.
+    pub fn set_default_extension(&mut self, extension: &str) -> Result<(), Error> {
+        self.default_extension = if extension.is_empty() {
+            None
+        } else {
+            Some(parse_extension(extension)?)
+        };
+        Ok(())
+    }
+
+    pub fn get_default_extension(&self) -> String {
+        self.default_extension.to_owned().unwrap_or_default()
+    }
+}
+
+// @desc: Extensions of the files treated as notes, and the one new notes are created with.
+//
+// @notes:
+//      -> The default extension always counts as a note extension, even if it isn't listed.
+#[derive(Debug, Clone)]
+pub struct NoteTypes {
+    extensions: Vec<String>,
+    default: String,
+}
+
+impl Default for NoteTypes {
+    fn default() -> Self {
+        NoteTypes {
+            extensions: vec!["md".to_string()],
+            default: "md".to_string(),
+        }
+    }
+}
+
+impl NoteTypes {
+    pub fn resolve(vault: &NoteTypesConfig, global: &NoteTypesConfig) -> Self {
+        let mut note_types = NoteTypes::default();
+
+        if let Some(extensions) = vault.extensions.as_ref().or(global.extensions.as_ref()) {
+            note_types.extensions = extensions.to_owned();
+        }
+        if let Some(default) = vault
+            .default_extension
+            .as_ref()
+            .or(global.default_extension.as_ref())
+        {
+            note_types.default = default.to_owned();
+        }
+
+        if !note_types.extensions.contains(&note_types.default) {
+            note_types
+                .extensions
+                .insert(0, note_types.default.to_owned());
+        }
+
+        note_types
+    }
+
+    pub fn is_note(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| self.is_extension(&extension.to_string_lossy()))
+    }
+
+    pub fn is_extension(&self, extension: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(extension))
+    }
+
+    // @desc: Generates the path of a note in location from its name, which may or may not end in
+    //        its extension.
+    //
+    // @notes:
+    //      -> Names without one of the extensions get the extension of an existing note with the
+    //         name (trying extensions in order), or the default one if there's no such note.
+    //      -> Extensions are appended rather than set, since note names can contain dots (e.g.
+    //         dates).
+    pub fn note_path(&self, location: &Path, name: &str) -> PathBuf {
+        let path = location.join(name);
+
+        self.candidates(&path)
+            .into_iter()
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| {
+                if self.is_note(&path) {
+                    path
+                } else {
+                    with_extension(&path, &self.default)
+                }
+            })
+    }
+
+    // @desc: Generates the paths a note could be at, from a path that may or may not end in its
+    //        extension. Paths already ending in one of the extensions are the only candidate.
+    pub fn candidates(&self, path: &Path) -> Vec<PathBuf> {
+        if self.is_note(path) {
+            return vec![path.to_path_buf()];
+        }
+
+        self.extensions
+            .iter()
+            .map(|extension| with_extension(path, extension))
+            .collect()
+    }
+
+    // @desc: Appends an extension to a note name, making sure it's one of the note extensions.
+    pub fn add_extension(&self, name: &str, extension: &str) -> Result<String, Error> {
+        let extension = extension.trim_start_matches('.');
+        if !self.is_extension(extension) {
+            return Err(Error::InvalidExtension(extension.to_owned()));
+        }
+
+        if Path::new(name)
+            .extension()
+            .is_some_and(|current| current.eq_ignore_ascii_case(extension))
+        {
+            Ok(name.to_owned())
+        } else {
+            Ok(format!("{name}.{extension}"))
+        }
+    }
+
+    // @desc: Removes the extension from a note name, if it's one of the note extensions.
+    pub fn strip_extension(&self, name: &str) -> String {
+        let path = Path::new(name);
+        if self.is_note(path) {
+            path.with_extension("").to_string_lossy().to_string()
+        } else {
+            name.to_owned()
+        }
+    }
+
+    // @desc: Generates the name a note is displayed with, which only leaves the extension out
+    //        for notes with the default one (so that e.g. "todo.md" and "todo.txt" can be told
+    //        apart).
+    pub fn display_name(&self, path: &Path) -> String {
+        let is_default = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(&self.default));
+
+        if is_default {
+            path.file_stem().unwrap().to_string_lossy().to_string()
+        } else {
+            path.file_name().unwrap().to_string_lossy().to_string()
+        }
+    }
+}
+
+// @desc: Checks if a note is written in markdown, which is the only kind of note that gets
+//        frontmatter added to it by jot.
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("md"))
+}
+
+// @desc: Parses a comma separated list of extensions, e.g. "md, txt, .org".
+fn parse_extensions(value: &str) -> Result<Vec<String>, Error> {
+    let mut extensions = vec![];
+
+    for extension in value.split(',') {
+        let extension = parse_extension(extension)?;
+        if !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+    }
+
+    Ok(extensions)
+}
+
+// Leading dots are dropped, so ".txt" and "txt" are the same extension.
+fn parse_extension(value: &str) -> Result<String, Error> {
+    let extension = value.trim().trim_start_matches('.');
+
+    if extension.is_empty()
+        || extension
+            .chars()
+            .any(|char| !char.is_alphanumeric() && char != '_' && char != '-')
+    {
+        return Err(Error::InvalidExtension(value.trim().to_owned()));
+    }
+
+    Ok(extension.to_lowercase())
+}
+
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.to_path_buf();
+    path.as_mut_os_string().push(format!(".{extension}"));
+    path
+}
@@ -1,5 +1,10 @@
-use crate::enums::Item;
-use std::fmt::Display;
+use crate::{
+    enums::Item,
+    notes::{fuzzy::NoteMatch, links::LinkReport},
+    output::strip_ansi,
+};
+use serde_json::{json, Value};
+use std::{fmt::Display, path::PathBuf};
 
 #[allow(unused)]
 #[derive(Debug)]
@@ -17,12 +22,154 @@ pub enum Error {
     NotInsideVault,
     AlreadyInVault(String),
     OutOfBounds,
-    EditorNotFound,
+    EditorNotFound(String),
+    InvalidPattern(String),
+    IndexCorrupt,
+    InvalidFrontmatter(PathBuf),
+    MetaKeyNotFound(String),
+    LinkCheckFailed(LinkReport),
+    TemplateNotFound(String),
+    InvalidDate(String),
+    InvalidDateFormat(String),
+    NoPreviousFolder,
+    InvalidHistoryRef(String),
+    TrashItemNotFound(String),
+    InvalidAge(String),
+    NothingToUndo,
+    JournalCorrupt(PathBuf),
+    FileUnparsable(PathBuf, Option<usize>),
+    PermissionDenied(PathBuf),
+    MissingParent(PathBuf),
+    UnsupportedVersion(PathBuf, u32),
+    FolderNameMismatch(String),
+    AmbiguousNote(String, Vec<NoteMatch>),
+    InvalidExtension(String),
+    InvalidEditor(String),
+    InvalidBoolean(String),
+    ReservedVaultName(String),
     // MoveError will be removed if and when fs_extra::move_items() is replaced with a custom function.
+    #[allow(clippy::enum_variant_names)]
     MoveError(String), 
     Undefined(std::io::Error),
 }
 
+impl Error {
+    // @desc: Machine-readable code of the error, derived from its variant.
+    pub fn code(&self) -> &str {
+        match self {
+            Error::InvalidName => "invalid_name",
+            Error::SameName => "same_name",
+            Error::SameLocation => "same_location",
+            Error::PathNotFound => "path_not_found",
+            Error::PathNotAbsolute => "path_not_absolute",
+            Error::ItemAlreadyExists(..) => "item_already_exists",
+            Error::ItemNotFound(..) => "item_not_found",
+            Error::VaultAlreadyExists(_) => "vault_already_exists",
+            Error::VaultNotFound(_) => "vault_not_found",
+            Error::NotInsideVault => "not_inside_vault",
+            Error::AlreadyInVault(_) => "already_in_vault",
+            Error::OutOfBounds => "out_of_bounds",
+            Error::EditorNotFound(_) => "editor_not_found",
+            Error::InvalidPattern(_) => "invalid_pattern",
+            Error::IndexCorrupt => "index_corrupt",
+            Error::InvalidFrontmatter(_) => "invalid_frontmatter",
+            Error::MetaKeyNotFound(_) => "meta_key_not_found",
+            Error::LinkCheckFailed(_) => "link_check_failed",
+            Error::TemplateNotFound(_) => "template_not_found",
+            Error::InvalidDate(_) => "invalid_date",
+            Error::InvalidDateFormat(_) => "invalid_date_format",
+            Error::NoPreviousFolder => "no_previous_folder",
+            Error::InvalidHistoryRef(_) => "invalid_history_ref",
+            Error::TrashItemNotFound(_) => "trash_item_not_found",
+            Error::InvalidAge(_) => "invalid_age",
+            Error::NothingToUndo => "nothing_to_undo",
+            Error::JournalCorrupt(_) => "journal_corrupt",
+            Error::FileUnparsable(..) => "file_unparsable",
+            Error::PermissionDenied(_) => "permission_denied",
+            Error::MissingParent(_) => "missing_parent",
+            Error::UnsupportedVersion(..) => "unsupported_version",
+            Error::FolderNameMismatch(_) => "folder_name_mismatch",
+            Error::AmbiguousNote(..) => "ambiguous_note",
+            Error::InvalidExtension(_) => "invalid_extension",
+            Error::InvalidEditor(_) => "invalid_editor",
+            Error::InvalidBoolean(_) => "invalid_boolean",
+            Error::ReservedVaultName(_) => "reserved_vault_name",
+            Error::MoveError(_) => "move_error",
+            Error::Undefined(_) => "undefined",
+        }
+    }
+
+    // @desc: Exit code jot exits with on the error.
+    //
+    // @notes:
+    //      -> Codes are documented in the README and shouldn't change once released. New errors
+    //         get the next free code.
+    //      -> 1 is left for undefined errors and 2 for invalid arguments (reported by clap).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Undefined(_) => 1,
+            Error::InvalidName => 10,
+            Error::SameName => 11,
+            Error::SameLocation => 12,
+            Error::PathNotFound => 13,
+            Error::PathNotAbsolute => 14,
+            Error::ItemAlreadyExists(..) => 15,
+            Error::ItemNotFound(..) => 16,
+            Error::VaultAlreadyExists(_) => 17,
+            Error::VaultNotFound(_) => 18,
+            Error::NotInsideVault => 19,
+            Error::AlreadyInVault(_) => 20,
+            Error::OutOfBounds => 21,
+            Error::EditorNotFound(_) => 22,
+            Error::MoveError(_) => 23,
+            Error::InvalidPattern(_) => 24,
+            Error::IndexCorrupt => 25,
+            Error::InvalidFrontmatter(_) => 26,
+            Error::MetaKeyNotFound(_) => 27,
+            Error::LinkCheckFailed(_) => 28,
+            Error::TemplateNotFound(_) => 29,
+            Error::InvalidDate(_) => 30,
+            Error::InvalidDateFormat(_) => 31,
+            Error::NoPreviousFolder => 32,
+            Error::InvalidHistoryRef(_) => 33,
+            Error::TrashItemNotFound(_) => 34,
+            Error::InvalidAge(_) => 35,
+            Error::NothingToUndo => 36,
+            Error::JournalCorrupt(_) => 37,
+            Error::FileUnparsable(..) => 38,
+            Error::PermissionDenied(_) => 39,
+            Error::MissingParent(_) => 40,
+            Error::UnsupportedVersion(..) => 41,
+            Error::FolderNameMismatch(_) => 42,
+            Error::AmbiguousNote(..) => 43,
+            Error::InvalidExtension(_) => 44,
+            Error::InvalidEditor(_) => 45,
+            Error::InvalidBoolean(_) => 46,
+            Error::ReservedVaultName(_) => 47,
+        }
+    }
+
+    // @desc: Converts the error into a JSON object with its code and message.
+    //
+    // @notes:
+    //      -> Failed link checks also carry the report, and ambiguous notes the candidates.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "type": "error",
+            "code": self.code(),
+            "message": strip_ansi(&self.to_string()),
+        });
+
+        match self {
+            Error::LinkCheckFailed(report) => value["report"] = json!(report),
+            Error::AmbiguousNote(_, candidates) => value["candidates"] = json!(candidates),
+            _ => (),
+        }
+
+        value
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -45,7 +192,57 @@ impl Display for Error {
                 Error::NotInsideVault => "not inside a vault".to_string(),
                 Error::AlreadyInVault(name) => format!("already in vault {name}"),
                 Error::OutOfBounds => "path crosses the bounds of vault".to_string(),
-                Error::EditorNotFound => "editor not found".to_string(),
+                Error::EditorNotFound(editor) =>
+                    format!("editor \x1b[0;34m{editor}\x1b[0m not found"),
+                Error::InvalidEditor(command) =>
+                    format!("couldn't parse editor command line \x1b[0;34m{command}\x1b[0m"),
+                Error::InvalidBoolean(value) =>
+                    format!("invalid value {value}, expected true or false"),
+                Error::ReservedVaultName(name) => format!(
+                    "vaults can't be named \x1b[0;34m{name}\x1b[0m, as it's a 'jt vault' subcommand"
+                ),
+                Error::InvalidPattern(pattern) => format!("invalid search pattern {pattern}"),
+                Error::InvalidFrontmatter(path) =>
+                    format!("couldn't parse frontmatter of {}", path.display()),
+                Error::MetaKeyNotFound(key) => format!("key {key} not found in frontmatter"),
+                Error::LinkCheckFailed(report) => report.to_string(),
+                Error::TemplateNotFound(name) => format!("template {name} not found"),
+                Error::InvalidDate(date) => format!("invalid date {date}"),
+                Error::InvalidDateFormat(format) => format!("invalid date format {format}"),
+                Error::NoPreviousFolder => "no previous folder in history".to_string(),
+                Error::InvalidHistoryRef(reference) =>
+                    format!("{reference} doesn't match an entry of this type in history"),
+                Error::TrashItemNotFound(id) => format!("no item with id {id} in trash"),
+                Error::InvalidAge(age) =>
+                    format!("invalid age {age}, expected e.g. 12h, 30d or 2w"),
+                Error::NothingToUndo => "nothing to undo".to_string(),
+                Error::JournalCorrupt(path) => format!("journal at {} is corrupt", path.display()),
+                Error::FileUnparsable(path, line) => match line {
+                    Some(line) => format!("couldn't parse {} at line {line}", path.display()),
+                    None => format!("couldn't parse {}", path.display()),
+                },
+                Error::PermissionDenied(path) =>
+                    format!("permission denied for {}", path.display()),
+                Error::MissingParent(path) =>
+                    format!("folder containing {} doesn't exist", path.display()),
+                Error::UnsupportedVersion(path, version) => format!(
+                    "{} has version {version}, which is newer than this version of jot supports",
+                    path.display()
+                ),
+                Error::AmbiguousNote(name, candidates) => format!(
+                    "several notes match {name}, pass --first to open the best match:\n{}",
+                    candidates
+                        .iter()
+                        .map(|candidate| format!("  {candidate}"))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                ),
+                Error::InvalidExtension(extension) =>
+                    format!("\x1b[0;34m{extension}\x1b[0m isn't a valid note extension"),
+                Error::FolderNameMismatch(name) =>
+                    format!("folder of vault \x1b[0;34m{name}\x1b[0m has to be named after it"),
+                Error::IndexCorrupt =>
+                    "search index is corrupt, run 'jt index rebuild' to fix it".to_string(),
                 Error::MoveError(msg) => msg.to_owned(),
                 Error::Undefined(error) => format!("undefined error: {error}")
             }
@@ -1,19 +1,158 @@
-use crate::enums::{ConfigType, Item, VaultItem};
+use crate::{
+    enums::{ConfigType, Item, VaultItem},
+    notes::{
+        links::{Backlink, LinkUpdate},
+        search::SearchHit,
+    },
+    state::{
+        journal::JournalEntry,
+        trash::TrashEntry,
+        vaults::{
+            daily::DailyCalendar,
+            doctor::DoctorReport,
+            history::HistoryEntry,
+            listing::{FolderTree, VaultList},
+            scan::ScanReport,
+        },
+    },
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
 use std::fmt::Display;
 
 pub enum Message {
     VaultEntered(String),
+    VaultAdded(String),
+    VaultForgotten(String),
     ItemCreated(Item, String),
-    ItemRemoved(Item, String),
-    ItemRenamed(Item, String, String),
-    ItemMoved(Item, String),
+    ItemRemoved(Item, String, String),
+    ItemRestored(Item, String),
+    ItemRenamed(Item, String, String, LinkUpdate),
+    ItemMoved(Item, String, LinkUpdate),
     ItemVMoved(VaultItem, String, String),
     FolderChanged,
     Config(ConfigType, String),
     ConfigSet(ConfigType, String),
+    SearchResults(Vec<SearchHit>),
+    IndexRebuilt(usize),
+    Tags(Vec<(String, usize)>),
+    Backlinks(Vec<Backlink>),
+    LinksChecked,
+    DailyNotes(DailyCalendar),
+    History(Vec<HistoryEntry>),
+    Meta(Vec<(String, String)>),
+    MetaSet(String, String),
+    MetaRemoved(String),
+    Trash(Vec<TrashEntry>),
+    TrashEmptied(usize),
+    Undone(JournalEntry),
+    Log(Vec<JournalEntry>),
+    Vaults(VaultList),
+    VaultScan(ScanReport),
+    Doctor(DoctorReport),
+    Tree(FolderTree),
     Empty,
 }
 
+impl Message {
+    // @desc: Converts the message into a JSON object, with the kind of message under "type".
+    //
+    // @notes:
+    //      -> Messages without any data (e.g. after opening a note) are converted to an "ok"
+    //         object, so that every command outputs exactly one object.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Message::VaultEntered(name) => json!({"type": "vault_entered", "name": name}),
+            Message::VaultAdded(name) => json!({"type": "vault_added", "name": name}),
+            Message::VaultForgotten(name) => json!({"type": "vault_forgotten", "name": name}),
+            Message::ItemCreated(item_type, name) => {
+                json!({"type": "item_created", "item": item_type.full(), "name": name})
+            }
+            Message::ItemRemoved(item_type, name, id) => json!({
+                "type": "item_removed",
+                "item": item_type.full(),
+                "name": name,
+                "trash_id": id
+            }),
+            Message::ItemRestored(item_type, name) => {
+                json!({"type": "item_restored", "item": item_type.full(), "name": name})
+            }
+            Message::ItemRenamed(item_type, name, new_name, links_updated) => json!({
+                "type": "item_renamed",
+                "item": item_type.full(),
+                "name": name,
+                "new_name": new_name,
+                "links_updated": links_updated.get_updated(),
+                "links_failed": links_updated.get_failed()
+            }),
+            Message::ItemMoved(item_type, name, links_updated) => json!({
+                "type": "item_moved",
+                "item": item_type.full(),
+                "name": name,
+                "links_updated": links_updated.get_updated(),
+                "links_failed": links_updated.get_failed()
+            }),
+            Message::ItemVMoved(item_type, name, vault_name) => json!({
+                "type": "item_vmoved",
+                "item": item_type.full(),
+                "name": name,
+                "vault": vault_name
+            }),
+            Message::FolderChanged => json!({"type": "folder_changed"}),
+            Message::Config(config_type, value) => {
+                json!({"type": "config", "key": config_type.to_str(), "value": value})
+            }
+            Message::ConfigSet(config_type, value) => {
+                json!({"type": "config_set", "key": config_type.to_str(), "value": value})
+            }
+            Message::SearchResults(hits) => json!({"type": "search_results", "hits": hits}),
+            Message::IndexRebuilt(count) => json!({"type": "index_rebuilt", "notes": count}),
+            Message::Tags(tags) => json!({
+                "type": "tags",
+                "tags": tags
+                    .iter()
+                    .map(|(tag, count)| json!({"tag": tag, "count": count}))
+                    .collect::<Vec<Value>>()
+            }),
+            Message::Backlinks(backlinks) => {
+                json!({"type": "backlinks", "backlinks": backlinks})
+            }
+            Message::LinksChecked => json!({"type": "links_checked"}),
+            Message::DailyNotes(calendar) => tagged("daily_notes", calendar),
+            Message::History(history) => json!({
+                "type": "history",
+                "entries": history
+                    .iter()
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        let mut entry = serde_json::to_value(entry).unwrap();
+                        entry["ref"] = json!(format!("@{}", index + 1));
+                        entry
+                    })
+                    .collect::<Vec<Value>>()
+            }),
+            Message::Meta(fields) => json!({
+                "type": "meta",
+                "fields": fields
+                    .iter()
+                    .map(|(key, value)| (key.to_owned(), json!(value)))
+                    .collect::<Map<String, Value>>()
+            }),
+            Message::MetaSet(key, value) => json!({"type": "meta_set", "key": key, "value": value}),
+            Message::MetaRemoved(key) => json!({"type": "meta_removed", "key": key}),
+            Message::Trash(entries) => json!({"type": "trash", "entries": entries}),
+            Message::TrashEmptied(count) => json!({"type": "trash_emptied", "deleted": count}),
+            Message::Undone(entry) => json!({"type": "undone", "entry": entry}),
+            Message::Log(entries) => json!({"type": "log", "entries": entries}),
+            Message::Vaults(vaults) => tagged("vaults", vaults),
+            Message::VaultScan(report) => tagged("vault_scan", report),
+            Message::Doctor(report) => tagged("doctor", report),
+            Message::Tree(tree) => tagged("tree", tree),
+            Message::Empty => json!({"type": "ok"}),
+        }
+    }
+}
+
 impl Display for Message {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -21,16 +160,26 @@ impl Display for Message {
             "{}",
             match self {
                 Message::VaultEntered(name) => format!("entered \x1b[0;34m{name}\x1b[0m"),
+                Message::VaultAdded(name) => format!("vault \x1b[0;34m{name}\x1b[0m added"),
+                Message::VaultForgotten(name) => format!("vault \x1b[0;34m{name}\x1b[0m forgotten"),
                 Message::ItemCreated(item_type, name) =>
                     format!("{} \x1b[0;34m{name}\x1b[0m created", item_type.full()),
-                Message::ItemRemoved(item_type, name) =>
-                    format!("{} \x1b[0;34m{name}\x1b[0m removed", item_type.full()),
-                Message::ItemRenamed(item_type, name, new_name) => format!(
-                    "{} \x1b[0;34m{name}\x1b[0m renamed to \x1b[0;34m{new_name}\x1b[0m",
+                Message::ItemRemoved(item_type, name, id) => format!(
+                    "{} \x1b[0;34m{name}\x1b[0m moved to trash (id \x1b[0;34m{id}\x1b[0m)",
+                    item_type.full()
+                ),
+                Message::ItemRestored(item_type, name) =>
+                    format!("{} \x1b[0;34m{name}\x1b[0m restored", item_type.full()),
+                Message::ItemRenamed(item_type, name, new_name, links_updated) => format!(
+                    "{} \x1b[0;34m{name}\x1b[0m renamed to \x1b[0;34m{new_name}\x1b[0m{}",
                     item_type.full(),
+                    links_updated_suffix(links_updated)
+                ),
+                Message::ItemMoved(item_type, name, links_updated) => format!(
+                    "{} \x1b[0;34m{name}\x1b[0m moved{}",
+                    item_type.full(),
+                    links_updated_suffix(links_updated)
                 ),
-                Message::ItemMoved(item_type, name) =>
-                    format!("{} \x1b[0;34m{name}\x1b[0m moved", item_type.full()),
                 Message::ItemVMoved(item_type, name, vault_name) => format!(
                     "{} \x1b[0;34m{name}\x1b[0m moved to vault \x1b[0;34m{vault_name}\x1b[0m",
                     item_type.full(),
@@ -42,8 +191,133 @@ impl Display for Message {
                     "set \x1b[0;34m{}\x1b[0m to \x1b[0;34m{value}\x1b[0m",
                     config_type.to_str()
                 ),
+                Message::SearchResults(hits) => {
+                    if hits.is_empty() {
+                        "no matches found".to_string()
+                    } else {
+                        hits.iter()
+                            .map(|hit| hit.to_string())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::IndexRebuilt(count) =>
+                    format!("index rebuilt with \x1b[0;34m{count}\x1b[0m notes"),
+                Message::Tags(tags) => {
+                    if tags.is_empty() {
+                        "no tags found".to_string()
+                    } else {
+                        tags.iter()
+                            .map(|(tag, count)| format!("\x1b[0;34m#{tag}\x1b[0m \t {count}"))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::Backlinks(backlinks) => {
+                    if backlinks.is_empty() {
+                        "no backlinks found".to_string()
+                    } else {
+                        backlinks
+                            .iter()
+                            .map(|backlink| backlink.to_string())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::LinksChecked => "no broken links or orphan notes found".to_string(),
+                Message::DailyNotes(calendar) => calendar.to_string(),
+                Message::History(history) => {
+                    if history.is_empty() {
+                        "history is empty".to_string()
+                    } else {
+                        history
+                            .iter()
+                            .enumerate()
+                            .map(|(index, entry)| {
+                                format!("\x1b[0;34m@{}\x1b[0m \t {entry}", index + 1)
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::Meta(fields) => {
+                    if fields.is_empty() {
+                        "no metadata".to_string()
+                    } else {
+                        fields
+                            .iter()
+                            .map(|(key, value)| format!("{key}: \x1b[0;34m{value}\x1b[0m"))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::MetaSet(key, value) =>
+                    format!("set \x1b[0;34m{key}\x1b[0m to \x1b[0;34m{value}\x1b[0m"),
+                Message::MetaRemoved(key) => format!("removed \x1b[0;34m{key}\x1b[0m"),
+                Message::Trash(entries) => {
+                    if entries.is_empty() {
+                        "trash is empty".to_string()
+                    } else {
+                        entries
+                            .iter()
+                            .map(|entry| entry.to_string())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::TrashEmptied(count) =>
+                    format!("deleted \x1b[0;34m{count}\x1b[0m items from trash"),
+                Message::Undone(entry) => format!("undid {}", entry.get_operation()),
+                Message::Log(entries) => {
+                    if entries.is_empty() {
+                        "journal is empty".to_string()
+                    } else {
+                        entries
+                            .iter()
+                            .map(|entry| entry.to_string())
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Message::Vaults(vaults) => vaults.to_string(),
+                Message::VaultScan(report) => report.to_string(),
+                Message::Doctor(report) => report.to_string(),
+                Message::Tree(tree) => tree.to_string(),
                 Message::Empty => "".to_string(),
             }
         )
     }
 }
+
+// @desc: Serializes a struct into a JSON object with the given type added to its fields.
+fn tagged<T: Serialize>(message_type: &str, value: &T) -> Value {
+    let mut value = serde_json::to_value(value).unwrap();
+    value["type"] = json!(message_type);
+    value
+}
+
+// @desc: Generates the part of a rename or move message telling which notes had links rewritten.
+//
+// @notes:
+//      -> Notes whose links couldn't be rewritten are listed, so they can be fixed by hand.
+fn links_updated_suffix(links_updated: &LinkUpdate) -> String {
+    let mut suffix = match links_updated.get_updated() {
+        0 => "".to_string(),
+        1 => ", links updated in \x1b[0;34m1\x1b[0m note".to_string(),
+        updated => format!(", links updated in \x1b[0;34m{updated}\x1b[0m notes"),
+    };
+
+    let failed = links_updated.get_failed();
+    if !failed.is_empty() {
+        let failed: Vec<String> = failed
+            .iter()
+            .map(|path| format!("\x1b[0;34m{}\x1b[0m", path.display()))
+            .collect();
+        suffix.push_str(&format!(
+            ", links couldn't be updated in {}",
+            failed.join(", ")
+        ));
+    }
+
+    suffix
+}
@@ -5,12 +5,24 @@ use std::fmt::Display;
 
 use error::Error;
 pub use message::Message;
+use regex::Regex;
 
 pub enum Output {
     Message(Message),
     Error(Error),
 }
 
+impl Output {
+    pub fn to_json(&self) -> String {
+        let value = match self {
+            Output::Message(msg) => msg.to_json(),
+            Output::Error(err) => err.to_json(),
+        };
+
+        value.to_string()
+    }
+}
+
 impl Display for Output {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -23,3 +35,17 @@ impl Display for Output {
         )
     }
 }
+
+// @desc: Prints a warning to stderr, for problems that don't stop the command, e.g. a note that
+//        can't be read.
+pub fn warn(msg: &str) {
+    eprintln!("\x1b[0;33mwarning\x1b[0m: {msg}");
+}
+
+// @desc: Removes colour codes from text, e.g. for messages of errors in JSON.
+pub fn strip_ansi(text: &str) -> String {
+    Regex::new("\x1b\\[[0-9;]*m")
+        .unwrap()
+        .replace_all(text, "")
+        .to_string()
+}
@@ -1,4 +1,4 @@
-use crate::enums::{ConfigType, Item, VaultItem};
+use crate::enums::{CheckType, ConfigType, IndexAction, Item, TrashAction, VaultItem};
 use clap::{AppSettings, Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -20,51 +20,88 @@ usage: jt <command>
 \x1b[0;34mcommands:\x1b[0m
 
 create items
-    \x1b[0;34mvault\x1b[0m, \x1b[0;34mvl\x1b[0m       create a vault or list vaults
+    \x1b[0;34mvault\x1b[0m, \x1b[0;34mvl\x1b[0m       create, add, scan, forget or list vaults
     create items in current folder
         \x1b[0;34mnote\x1b[0m, \x1b[0;34mnt\x1b[0m        create a note 
         \x1b[0;34mfolder\x1b[0m, \x1b[0;34mfd\x1b[0m      create a folder
 
 interact with items
     \x1b[0;34menter\x1b[0m, \x1b[0;34men\x1b[0m       enter a vault
-    \x1b[0;34mopen\x1b[0m, \x1b[0;34mop\x1b[0m        open a note from current folder
+    \x1b[0;34mopen\x1b[0m, \x1b[0;34mop\x1b[0m        open a note (or find one fuzzily)
     \x1b[0;34mopdir\x1b[0m, \x1b[0;34mod\x1b[0m       open current folder in file explorer
     \x1b[0;34mchdir\x1b[0m, \x1b[0;34mcd\x1b[0m       change folder within current vault
+    \x1b[0;34mhistory\x1b[0m, \x1b[0;34mhs\x1b[0m     list recent folders and notes
+    \x1b[0;34mtoday\x1b[0m, \x1b[0;34mtd\x1b[0m       open today's daily note
+    \x1b[0;34myesterday\x1b[0m, \x1b[0;34myd\x1b[0m   open yesterday's daily note
+    \x1b[0;34mtomorrow\x1b[0m, \x1b[0;34mtm\x1b[0m    open tomorrow's daily note
+    \x1b[0;34mdaily\x1b[0m, \x1b[0;34mdl\x1b[0m       open a daily note or list daily notes of a month
     \x1b[0;34mlist\x1b[0m, \x1b[0;34mls\x1b[0m        list items in current folder
+    \x1b[0;34mtags\x1b[0m, \x1b[0;34mtg\x1b[0m        list tags in current vault
+    \x1b[0;34mbacklinks\x1b[0m, \x1b[0;34mbl\x1b[0m   list notes linking to a note
+    \x1b[0;34mcheck\x1b[0m, \x1b[0;34mck\x1b[0m       check current vault for broken links and orphan notes
+    \x1b[0;34mmeta\x1b[0m, \x1b[0;34mmt\x1b[0m        display or edit a note's metadata
+    \x1b[0;34msearch\x1b[0m, \x1b[0;34msr\x1b[0m      search notes in current vault
+    \x1b[0;34mindex\x1b[0m, \x1b[0;34mix\x1b[0m       manage search index of current vault
 
 perform fs operations on items
     \x1b[0;34mremove\x1b[0m, \x1b[0;34mrm\x1b[0m      remove an item 
     \x1b[0;34mrename\x1b[0m, \x1b[0;34mrn\x1b[0m      rename an item 
     \x1b[0;34mmove\x1b[0m, \x1b[0;34mmv\x1b[0m        move an item to a new location
     \x1b[0;34mvmove\x1b[0m, \x1b[0;34mvm\x1b[0m       move an item to a different vault
+    \x1b[0;34mtrash\x1b[0m, \x1b[0;34mtr\x1b[0m       list, restore or permanently delete removed items
+    \x1b[0;34mundo\x1b[0m, \x1b[0;34mud\x1b[0m        undo the last operation
+    \x1b[0;34mlog\x1b[0m, \x1b[0;34mlg\x1b[0m         list recent operations
 
 config
     \x1b[0;34mconfig\x1b[0m, \x1b[0;34mcf\x1b[0m      display, set or open config
+    \x1b[0;34mdoctor\x1b[0m, \x1b[0;34mdr\x1b[0m      check vaults for problems and fix them
+
+output
+    use \x1b[0;34m--json\x1b[0m flag with any command to get its output as JSON
+
+select vault
+    use \x1b[0;34m--vault\x1b[0m <vault name> flag (or \x1b[0;34mJOT_VAULT\x1b[0m env var) with any command to run it
+    in a vault other than the current one, without entering it
 
 get help 
     use \x1b[0;34mhelp\x1b[0m or \x1b[0;34m-h\x1b[0m and \x1b[0;34m--help\x1b[0m flags along with a command to get corresponding help"))]
 pub struct Args {
     #[clap(subcommand)]
     pub command: Command,
+    /// output messages and errors as JSON
+    #[clap(parse(from_flag), long = "json", global = true)]
+    pub json: bool,
+    /// use this vault instead of the current one, without entering it
+    #[clap(
+        value_parser,
+        long = "vault",
+        value_name = "vault name",
+        global = true,
+        env = "JOT_VAULT"
+    )]
+    pub vault: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
-    /// create a vault or list vaults
+    /// create, add, scan, forget or list vaults
     #[clap(override_usage(
-        "jt vault\n    jt vault -l\n    jt vault <vault name> <vault location>"
+        "jt vault\n    jt vault -l\n    jt vault <vault name> <vault location>\n    jt vault add <vault name> <vault path>\n    jt vault forget <vault name>\n    jt vault scan <root> [--depth <depth>]"
     ))]
     #[clap(alias = "vl")]
+    #[clap(args_conflicts_with_subcommands = true)]
     Vault {
         /// show vaults' location
         #[clap(parse(from_flag), short = 'l')]
         show_loc: bool,
-        /// name for new vault
+        /// name for new vault (can't be add, forget or scan)
         #[clap(value_parser, name = "vault name", requires = "vault location")]
         name: Option<String>,
         /// absolute path to location of new vault
         #[clap(value_parser, name = "vault location")]
         location: Option<PathBuf>,
+        #[clap(subcommand)]
+        action: Option<VaultAction>,
     },
     /// enter a vault.
     #[clap(alias = "en")]
@@ -74,27 +111,50 @@ pub enum Command {
         name: String,
     },
     /// create a note
-    #[clap(override_usage("jt note\n    jt note [note name]"))]
+    #[clap(override_usage(
+        "jt note\n    jt note [note name] [--parents]\n    jt note [note name] -t <template> [-v <key=value>...]"
+    ))]
     #[clap(alias = "nt")]
     Note {
-        /// name for new note (to be created in the current folder)
+        /// name for new note (to be created in the current folder), or a path to it
         #[clap(value_parser, name = "note name")]
         name: String,
+        /// create missing folders in the path to the note
+        #[clap(parse(from_flag), long = "parents", short = 'p')]
+        parents: bool,
+        /// extension of the note, instead of the default one (e.g. txt)
+        #[clap(value_parser, long = "ext", short = 'e')]
+        ext: Option<String>,
+        /// name of template to create the note from
+        #[clap(value_parser, long = "template", short = 't')]
+        template: Option<String>,
+        /// value for a custom template variable
+        #[clap(value_parser = parse_var, long = "var", short = 'v', name = "key=value")]
+        vars: Vec<(String, String)>,
     },
-    /// open a note (from the current folder)
+    /// open a note (from the current folder, or the best fuzzy match in the vault)
     #[clap(alias = "op")]
     Open {
-        /// name of note to be opened
+        /// name of note to be opened, part of it, or a reference to history like @1
         #[clap(value_parser, name = "note name")]
         name: String,
+        /// open the best match without asking when several notes match
+        #[clap(parse(from_flag), long = "first")]
+        first: bool,
+        /// line to open the note at, for editors set up with {line}
+        #[clap(value_parser, long = "line", short = 'l')]
+        line: Option<usize>,
     },
     /// create a folder
-    #[clap(override_usage("jt folder\n    jt folder [folder name]"))]
+    #[clap(override_usage("jt folder\n    jt folder [folder name] [--parents]"))]
     #[clap(alias = "fd")]
     Folder {
-        /// name for new folder (to be created in the current folder)
+        /// name for new folder (to be created in the current folder), or a path to it
         #[clap(value_parser, name = "folder name")]
         name: String,
+        /// create missing folders in the path to the folder
+        #[clap(parse(from_flag), long = "parents", short = 'p')]
+        parents: bool,
     },
     /// open current folder in file explorer
     #[clap(alias = "od")]
@@ -102,10 +162,34 @@ pub enum Command {
     /// change folder within current vault
     #[clap(alias = "cd")]
     Chdir {
-        /// path to folder to switch to (from current folder)
-        #[clap(value_parser, name = "folder path")]
+        /// path to folder to switch to (from current folder), - for previous folder or a
+        /// reference to history like @3
+        #[clap(value_parser, name = "folder path", allow_hyphen_values = true)]
         path: PathBuf,
     },
+    /// list recently visited folders and opened notes in current vault
+    #[clap(alias = "hs")]
+    History,
+    /// open today's daily note (created if missing)
+    #[clap(alias = "td")]
+    Today,
+    /// open yesterday's daily note (created if missing)
+    #[clap(alias = "yd")]
+    Yesterday,
+    /// open tomorrow's daily note (created if missing)
+    #[clap(alias = "tm")]
+    Tomorrow,
+    /// open the daily note of a date or list daily notes of a month
+    #[clap(override_usage("jt daily <date>\n    jt daily -l\n    jt daily -l <month>"))]
+    #[clap(alias = "dl")]
+    Daily {
+        /// list days of a month that have daily notes
+        #[clap(parse(from_flag), short = 'l', long = "list")]
+        list: bool,
+        /// date of daily note (YYYY-MM-DD), or month to list (YYYY-MM) with -l
+        #[clap(value_parser, name = "date", required_unless_present = "list")]
+        date: Option<String>,
+    },
     /// remove an item
     #[clap(alias = "rm")]
     Remove {
@@ -128,6 +212,9 @@ pub enum Command {
         /// new name of item
         #[clap(value_parser, name = "new name")]
         new_name: String,
+        /// don't rewrite links pointing to a renamed note or folder
+        #[clap(parse(from_flag), long = "no-update-links")]
+        no_update_links: bool,
     },
     /// move an item
     #[clap(alias = "mv")]
@@ -141,6 +228,9 @@ pub enum Command {
         /// path to new location of item (current folder as root in case of note or folder).
         #[clap(value_parser, name = "new location")]
         new_location: PathBuf,
+        /// don't rewrite links pointing to a moved note or folder
+        #[clap(parse(from_flag), long = "no-update-links")]
+        no_update_links: bool,
     },
     /// move notes and folders to a different vault
     #[clap(alias = "vm")]
@@ -161,6 +251,100 @@ pub enum Command {
         // list note(s) (or nt) | folder(s) (or fd)
         #[clap(value_enum, value_parser, name = "item type")]
         item_type: Option<VaultItem>,
+        /// only list notes with this tag (and folders containing them)
+        #[clap(value_parser, long = "tag", short = 't')]
+        tag: Option<String>,
+    },
+    /// list tags in current vault along with the number of notes using them
+    #[clap(alias = "tg")]
+    Tags,
+    /// list notes that link to a note (from the current folder)
+    #[clap(alias = "bl")]
+    Backlinks {
+        /// name of note to find links to
+        #[clap(value_parser, name = "note name")]
+        name: String,
+    },
+    /// check current vault for broken links and orphan notes
+    #[clap(alias = "ck")]
+    Check {
+        /// check links
+        #[clap(value_enum, value_parser, name = "check type")]
+        check_type: CheckType,
+    },
+    /// display or edit a note's metadata (from the current folder)
+    #[clap(override_usage(
+        "jt meta <note name>\n    jt meta <note name> [key] [value]\n    jt meta -d <note name> <key>"
+    ))]
+    #[clap(alias = "mt")]
+    Meta {
+        /// name of note
+        #[clap(value_parser, name = "note name")]
+        name: String,
+        /// frontmatter field to display, set or remove
+        #[clap(value_parser, name = "key")]
+        key: Option<String>,
+        /// pass a value if field needs to be updated (comma separated for tags and aliases)
+        #[clap(value_parser, name = "value")]
+        value: Option<String>,
+        /// remove the field
+        #[clap(
+            parse(from_flag),
+            short = 'd',
+            requires = "key",
+            conflicts_with = "value"
+        )]
+        delete: bool,
+    },
+    /// search notes in current vault
+    #[clap(alias = "sr")]
+    Search {
+        /// text to search for (matched case-insensitively)
+        #[clap(value_parser, name = "query")]
+        query: String,
+        /// treat query as a regular expression
+        #[clap(parse(from_flag), short = 'r')]
+        regex: bool,
+    },
+    /// manage search index of current vault
+    #[clap(alias = "ix")]
+    Index {
+        /// rebuild the index from scratch
+        #[clap(value_enum, value_parser, name = "action")]
+        action: IndexAction,
+    },
+    /// list, restore or permanently delete removed items
+    #[clap(override_usage(
+        "jt trash list\n    jt trash restore <id>\n    jt trash empty [--older-than <age>]"
+    ))]
+    #[clap(alias = "tr")]
+    Trash {
+        /// list | restore | empty
+        #[clap(value_enum, value_parser, name = "action")]
+        action: TrashAction,
+        /// id of item to be restored
+        #[clap(value_parser, name = "id", required_if_eq("action", "restore"))]
+        id: Option<String>,
+        /// only delete items removed longer ago than this (e.g. 12h, 30d, 2w)
+        #[clap(value_parser, long = "older-than", name = "age")]
+        older_than: Option<String>,
+    },
+    /// undo the last operation (in current vault or on vaults and config)
+    #[clap(alias = "ud")]
+    Undo,
+    /// list recent operations (in current vault and on vaults and config)
+    #[clap(alias = "lg")]
+    Log {
+        /// number of operations to list
+        #[clap(value_parser, short = 'n', default_value_t = 20)]
+        count: usize,
+    },
+    /// check vaults for problems and fix them
+    #[clap(alias = "dr")]
+    Doctor {
+        /// fix all problems that can be fixed, without asking
+        #[clap(parse(from_flag), long = "fix")]
+        fix: bool,
     },
     /// display, set or open config
     #[clap(override_usage("jt config <config type>\n    jt config <config type> [config value]"))]
@@ -176,3 +360,40 @@ pub enum Command {
     /// 🆘 show this help message or help for given command.
     Help,
 }
+
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    match var.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_owned(), value.to_owned()))
+        }
+        _ => Err("expected a variable in the form key=value".to_string()),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum VaultAction {
+    /// register an existing folder as a vault, without touching its contents
+    Add {
+        /// name of the vault (same as name of the folder)
+        #[clap(value_parser, name = "vault name")]
+        name: String,
+        /// absolute path to the folder
+        #[clap(value_parser, name = "vault path")]
+        path: PathBuf,
+    },
+    /// unregister a vault, without deleting it
+    Forget {
+        /// name of the vault to forget
+        #[clap(value_parser, name = "vault name")]
+        name: String,
+    },
+    /// find vaults under a folder and register them
+    Scan {
+        /// absolute path to the folder to scan
+        #[clap(value_parser, name = "root")]
+        root: PathBuf,
+        /// how many levels of folders below root to scan
+        #[clap(value_parser, long = "depth", default_value_t = 3)]
+        depth: usize,
+    },
+}
@@ -1,24 +1,43 @@
-use crate::{enums::ConfigType, output::error::Error, traits::FileIO, utils::run_editor};
+use crate::{
+    enums::ConfigType, notes::types::NoteTypesConfig, output::error::Error, state::editor::Editor,
+    traits::FileIO,
+};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
+use toml::{value::Table, Value};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
-    editor: String,
+    version: u32,
+    #[serde(default)]
+    editor: Option<String>,
     conflict: bool,
+    #[serde(default)]
+    default_template: Option<String>,
+    #[serde(default)]
+    note_types: NoteTypesConfig,
+    // Editors for notes with certain extensions, e.g. "org" = "emacs -nw".
+    #[serde(default)]
+    editors: BTreeMap<String, String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            editor: "nvim".to_string(),
+            version: Self::VERSION,
+            editor: None,
             conflict: true,
+            default_template: None,
+            note_types: NoteTypesConfig::default(),
+            editors: BTreeMap::new(),
         }
     }
 }
 
 impl FileIO for Config {
+    const VERSION: u32 = 2;
+
     fn path(&self) -> PathBuf {
         if let Some(dirs) = ProjectDirs::from("com", "", "jot") {
             let mut path = dirs.config_dir().to_path_buf();
@@ -28,50 +47,136 @@ impl FileIO for Config {
             panic!("config path couldn't be generated")
         }
     }
+
+    // @notes:
+    //      -> 1 -> 2: "nvim" used to be the default editor and was written to every config, which
+    //         would keep $VISUAL and $EDITOR from being used. Configs that set it on purpose still
+    //         get it, as it's the fallback, unless either of them is set.
+    fn migrate(version: u32, data: &mut Table) {
+        if version == 1 && data.get("editor").and_then(Value::as_str) == Some("nvim") {
+            data.remove("editor");
+        }
+    }
 }
 
 impl Config {
-    pub fn get_editor_data(&self) -> (&String, bool) {
-        (&self.editor, self.conflict)
+    pub fn get_editor(&self) -> Editor<'_> {
+        Editor::new(self.editor.as_ref(), &self.editors, self.conflict)
     }
 
-    pub fn set_config(&mut self, config_type: &ConfigType, value: &String) {
+    pub fn set_config(&mut self, config_type: &ConfigType, value: &String) -> Result<(), Error> {
         match config_type {
             ConfigType::Editor => self.set_editor(value.to_owned()),
             ConfigType::Conflict => self.set_conflict(value.to_owned()),
+            ConfigType::DefaultTemplate => self.set_default_template(value.to_owned()),
+            ConfigType::Extensions => self.modify(|config| config.note_types.set_extensions(value)),
+            ConfigType::DefaultExtension => {
+                self.modify(|config| config.note_types.set_default_extension(value))
+            }
+            _ => Ok(()),
         }
     }
 
     pub fn get_config(&self, config_type: &ConfigType) -> String {
         match config_type {
-            ConfigType::Editor => self.get_editor().to_owned(),
+            ConfigType::Editor => self.editor.to_owned().unwrap_or_default(),
             ConfigType::Conflict => match self.get_conflict() {
                 true => "true".to_string(),
                 false => "false".to_string(),
             },
+            ConfigType::DefaultTemplate => self
+                .get_default_template()
+                .map(|template| template.to_owned())
+                .unwrap_or_default(),
+            ConfigType::Extensions => self.note_types.get_extensions(),
+            ConfigType::DefaultExtension => self.note_types.get_default_extension(),
+            _ => "".to_string(),
         }
     }
 
-    pub fn open_config(&self) -> Result<(), Error> {
-        run_editor(self.get_editor_data(), &self.path())?;
-        Ok(())
+    pub fn get_default_template(&self) -> Option<&String> {
+        self.default_template.as_ref()
     }
 
-    fn get_editor(&self) -> &String {
-        &self.editor
+    pub fn get_note_types(&self) -> &NoteTypesConfig {
+        &self.note_types
     }
 
-    fn set_editor(&mut self, editor: String) {
-        self.editor = editor;
-        self.store()
+    // @desc: Generates the location of global templates, which sits next to the config file.
+    pub fn get_templates_dir(&self) -> PathBuf {
+        self.path().with_file_name("templates")
+    }
+
+    pub fn open_config(&self) -> Result<(), Error> {
+        self.get_editor().open(&self.path(), None)
+    }
+
+    // An empty value unsets the editor, so that $VISUAL or $EDITOR is used.
+    fn set_editor(&mut self, editor: String) -> Result<(), Error> {
+        self.modify(|config| {
+            config.editor = if editor.is_empty() {
+                None
+            } else {
+                Some(editor)
+            };
+            Ok(())
+        })
     }
 
     fn get_conflict(&self) -> &bool {
         &self.conflict
     }
 
-    fn set_conflict(&mut self, conflict: String) {
-        self.conflict = conflict.parse().unwrap();
-        self.store()
+    fn set_conflict(&mut self, conflict: String) -> Result<(), Error> {
+        let conflict = match conflict.parse() {
+            Ok(conflict) => conflict,
+            Err(_) => return Err(Error::InvalidBoolean(conflict)),
+        };
+
+        self.modify(|config| {
+            config.conflict = conflict;
+            Ok(())
+        })
+    }
+
+    // An empty value unsets the default template.
+    fn set_default_template(&mut self, template: String) -> Result<(), Error> {
+        self.modify(|config| {
+            config.default_template = if template.is_empty() {
+                None
+            } else {
+                Some(template)
+            };
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn migrate_drops_the_old_default_editor() {
+        for content in [
+            "editor = 'nvim'\nconflict = true\n",
+            "version = 1\neditor = 'nvim'\nconflict = true\n",
+        ] {
+            let (config, migrated) = Config::parse(Path::new("config"), content).unwrap();
+
+            assert!(migrated);
+            assert_eq!(config.version, Config::VERSION);
+            assert_eq!(config.editor, None);
+        }
+    }
+
+    #[test]
+    fn migrate_keeps_other_editors() {
+        let content = "version = 1\neditor = 'code --wait'\nconflict = false\n";
+        let (config, _) = Config::parse(Path::new("config"), content).unwrap();
+
+        assert_eq!(config.editor, Some("code --wait".to_string()));
+        assert!(!config.conflict);
     }
 }
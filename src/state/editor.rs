@@ -0,0 +1,246 @@
+use crate::output::error::Error;
+use std::{collections::BTreeMap, env::var, path::Path, process::Command};
+
+// Used when no editor is set in config or through $VISUAL and $EDITOR.
+const FALLBACK_EDITOR: &str = "nvim";
+
+// @desc: Editor settings from config, used to open notes (and the config file itself).
+//
+// @notes:
+//      -> "command" is a shell-style command line, like "code --wait" or "nvim +{line} {file}".
+//      -> "overrides" maps note extensions to the command line used for notes with them.
+//      -> "conflict" tells whether the editor takes over the terminal, in which case jot waits for
+//         it to exit.
+pub struct Editor<'a> {
+    command: Option<&'a String>,
+    overrides: &'a BTreeMap<String, String>,
+    conflict: bool,
+}
+
+impl<'a> Editor<'a> {
+    pub fn new(
+        command: Option<&'a String>,
+        overrides: &'a BTreeMap<String, String>,
+        conflict: bool,
+    ) -> Self {
+        Editor {
+            command,
+            overrides,
+            conflict,
+        }
+    }
+
+    // @desc: Opens a file in the editor, at a line if one is passed.
+    pub fn open(&self, path: &Path, line: Option<usize>) -> Result<(), Error> {
+        let command = self.command_for(path);
+        let args = build_args(&command, path, line)?;
+
+        if let Err(error) = run_editor_collect(&args, self.conflict) {
+            return Err(match error.kind() {
+                std::io::ErrorKind::NotFound => Error::EditorNotFound(args[0].to_owned()),
+                _ => Error::Undefined(error),
+            });
+        }
+
+        Ok(())
+    }
+
+    // @desc: Picks the command line used to open a file: the override for its extension, the
+    //        editor from config, $VISUAL, $EDITOR and finally nvim, in that order.
+    fn command_for(&self, path: &Path) -> String {
+        let extension = path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+
+        self.overrides
+            .get(&extension)
+            .or(self.command)
+            .filter(|command| !command.trim().is_empty())
+            .map(|command| command.to_owned())
+            .or_else(|| env_editor("VISUAL"))
+            .or_else(|| env_editor("EDITOR"))
+            .unwrap_or_else(|| FALLBACK_EDITOR.to_string())
+    }
+}
+
+// @desc: Splits a command line into the program and its arguments, filling in placeholders.
+//
+// @notes:
+//      -> "{file}" is replaced by the path of the file, and "{line}" by the line to open it at (1
+//         if none was passed). The path is added as the last argument if "{file}" isn't used.
+//      -> Placeholders are filled in after splitting, so paths with spaces stay one argument.
+//      -> See split_command() for how the command line is split.
+//
+// @example:
+//      @conditions:
+//          -> command: "nvim +{line} {file}"
+//          -> path: "/vault/my note.md"
+//          -> line: Some(12)
+//
+//      @returns:
+//          -> ["nvim", "+12", "/vault/my note.md"] [Vec<String>]
+fn build_args(command: &str, path: &Path, line: Option<usize>) -> Result<Vec<String>, Error> {
+    let args = match split_command(command) {
+        Some(args) if !args.is_empty() => args,
+        _ => return Err(Error::InvalidEditor(command.to_owned())),
+    };
+
+    let file = path.to_string_lossy();
+    let line = line.unwrap_or(1).to_string();
+    let has_file = args.iter().any(|arg| arg.contains("{file}"));
+
+    let mut args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.replace("{file}", &file).replace("{line}", &line))
+        .collect();
+    if !has_file {
+        args.push(file.to_string());
+    }
+
+    Ok(args)
+}
+
+// @desc: Splits a command line into the program and its arguments. Returns None if quotes aren't
+//        closed.
+//
+// @notes:
+//      -> A command line naming an existing file is the program alone, so that paths with spaces
+//         (e.g. "C:\Program Files\Microsoft VS Code\Code.exe") don't need quoting.
+//      -> Backslashes separate folders on Windows, so they're only treated as escapes (as in a
+//         shell) on other systems.
+fn split_command(command: &str) -> Option<Vec<String>> {
+    let program = command.trim();
+    if Path::new(program).is_file() {
+        return Some(vec![program.to_owned()]);
+    }
+
+    if cfg!(windows) {
+        split_without_escapes(command)
+    } else {
+        shell_words::split(command).ok()
+    }
+}
+
+// @desc: Splits a command line on whitespace, keeping double quoted parts together. Backslashes
+//        are kept as they are.
+//
+// @example:
+//      @conditions:
+//          -> command: "\"C:\Program Files\Vim\vim.exe\" +{line}"
+//
+//      @returns:
+//          -> ["C:\Program Files\Vim\vim.exe", "+{line}"] [Vec<String>]
+fn split_without_escapes(command: &str) -> Option<Vec<String>> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+
+    for char in command.chars() {
+        match char {
+            '"' => {
+                quoted = !quoted;
+                arg.get_or_insert_with(String::new);
+            }
+            _ if char.is_whitespace() && !quoted => args.extend(arg.take()),
+            _ => arg.get_or_insert_with(String::new).push(char),
+        }
+    }
+
+    if quoted {
+        return None;
+    }
+
+    args.extend(arg);
+    Some(args)
+}
+
+fn env_editor(name: &str) -> Option<String> {
+    var(name).ok().filter(|editor| !editor.trim().is_empty())
+}
+
+fn run_editor_collect(args: &[String], conflict: bool) -> Result<(), std::io::Error> {
+    let mut cmd = Command::new(&args[0]).args(&args[1..]).spawn()?;
+
+    if conflict {
+        cmd.wait()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env::temp_dir, fs};
+
+    #[test]
+    fn split_without_escapes_keeps_windows_paths() {
+        assert_eq!(
+            split_without_escapes(r#""C:\Program Files\Microsoft VS Code\Code.exe" --wait"#),
+            Some(vec![
+                r"C:\Program Files\Microsoft VS Code\Code.exe".to_string(),
+                "--wait".to_string(),
+            ])
+        );
+        assert_eq!(
+            split_without_escapes(r"C:\Tools\vim.exe  +{line} {file}"),
+            Some(vec![
+                r"C:\Tools\vim.exe".to_string(),
+                "+{line}".to_string(),
+                "{file}".to_string(),
+            ])
+        );
+        assert_eq!(
+            split_without_escapes(r#"code --title """#),
+            Some(vec![
+                "code".to_string(),
+                "--title".to_string(),
+                "".to_string()
+            ])
+        );
+        assert_eq!(split_without_escapes(r#""C:\Program Files\vim.exe"#), None);
+    }
+
+    #[test]
+    fn build_args_takes_an_existing_file_as_the_program() {
+        let folder = temp_dir().join(format!("jot editor test {}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let program = folder.join("My Editor");
+        fs::write(&program, "").unwrap();
+
+        let args = build_args(
+            &program.to_string_lossy(),
+            Path::new("/vault/note.md"),
+            None,
+        );
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(
+            args.unwrap(),
+            vec![
+                program.to_string_lossy().to_string(),
+                "/vault/note.md".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn build_args_fills_in_placeholders() {
+        let args = build_args(
+            "nvim +{line} {file}",
+            Path::new("/vault/my note.md"),
+            Some(12),
+        );
+
+        assert_eq!(
+            args.unwrap(),
+            vec![
+                "nvim".to_string(),
+                "+12".to_string(),
+                "/vault/my note.md".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    enums::{ConfigType, Item},
+    output::error::Error,
+    traits::FileIO,
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+// Format used for the time operations were performed at. Milliseconds are kept so that entries
+// of the vault's journal and the global one can be ordered.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+// @desc: A mutating operation, with everything needed to reverse it.
+//
+// @notes:
+//      -> Paths of notes and folders are relative to the root of the vault, while paths of
+//         vaults are absolute.
+//      -> AddVault and ForgetVault only (un)register the vault at "path", its folder is left as
+//         it is.
+//      -> Relink only points the registration of a vault from "path" to "new_path" (where it was
+//         found by a scan), neither folder is touched.
+//      -> "value" of ConfigSet is the value the config had before it was set.
+//      -> Undo marks the entry with the given id as undone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Operation {
+    Create {
+        item: Item,
+        path: PathBuf,
+    },
+    Remove {
+        item: Item,
+        path: PathBuf,
+        trash_id: String,
+    },
+    Rename {
+        item: Item,
+        path: PathBuf,
+        new_path: PathBuf,
+        update_links: bool,
+    },
+    Move {
+        item: Item,
+        path: PathBuf,
+        new_path: PathBuf,
+        update_links: bool,
+    },
+    VMove {
+        item: Item,
+        path: PathBuf,
+        vault: String,
+    },
+    ChangeFolder {
+        folder: PathBuf,
+        new_folder: PathBuf,
+    },
+    AddVault {
+        path: PathBuf,
+    },
+    ForgetVault {
+        path: PathBuf,
+    },
+    Relink {
+        path: PathBuf,
+        new_path: PathBuf,
+    },
+    ConfigSet {
+        key: ConfigType,
+        value: String,
+        new_value: String,
+    },
+    Undo {
+        id: String,
+    },
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Create { item, path } => {
+                write!(f, "create {} {}", item.full(), display_path(path))
+            }
+            Operation::Remove {
+                item,
+                path,
+                trash_id,
+            } => write!(
+                f,
+                "remove {} {} (trash id {trash_id})",
+                item.full(),
+                display_path(path)
+            ),
+            Operation::Rename {
+                item,
+                path,
+                new_path,
+                ..
+            } => write!(
+                f,
+                "rename {} {} to {}",
+                item.full(),
+                display_path(path),
+                display_path(new_path)
+            ),
+            Operation::Move {
+                item,
+                path,
+                new_path,
+                ..
+            } => write!(
+                f,
+                "move {} {} to {}",
+                item.full(),
+                display_path(path),
+                display_path(new_path)
+            ),
+            Operation::VMove { item, path, vault } => write!(
+                f,
+                "move {} {} to vault {vault}",
+                item.full(),
+                display_path(path)
+            ),
+            Operation::ChangeFolder { folder, new_folder } => write!(
+                f,
+                "change folder from {} to {}",
+                display_path(folder),
+                display_path(new_folder)
+            ),
+            Operation::AddVault { path } => write!(f, "add vault {}", path.display()),
+            Operation::ForgetVault { path } => write!(f, "forget vault {}", path.display()),
+            Operation::Relink { path, new_path } => write!(
+                f,
+                "relink vault {} to {}",
+                path.display(),
+                new_path.display()
+            ),
+            Operation::ConfigSet {
+                key,
+                value,
+                new_value,
+            } => write!(f, "set {} from '{value}' to '{new_value}'", key.to_str()),
+            Operation::Undo { id } => write!(f, "undo {id}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    id: String,
+    time: String,
+    operation: Operation,
+}
+
+impl JournalEntry {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_time(&self) -> &str {
+        &self.time
+    }
+
+    pub fn get_operation(&self) -> &Operation {
+        &self.operation
+    }
+}
+
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Milliseconds are left out when displaying.
+        let time = self.time.split('.').next().unwrap_or_default();
+        write!(
+            f,
+            "\x1b[0;34m{}\x1b[0m \t {time} \t {}",
+            self.id, self.operation
+        )
+    }
+}
+
+// @desc: Journal of operations, kept per vault (in ".jot/journal") and globally for vaults and
+//        app config (next to the data file).
+//
+// @notes:
+//      -> Entries are added through FileIO::modify(), so that concurrent invocations take turns
+//         and each entry gets an id of its own.
+//      -> Ids of the global journal are prefixed with "g", like ids of the global trash are.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    prefix: String,
+    version: u32,
+    #[serde(default)]
+    entries: Vec<JournalEntry>,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Journal {
+            path: PathBuf::new(),
+            prefix: String::new(),
+            version: Self::VERSION,
+            entries: vec![],
+        }
+    }
+}
+
+impl FileIO for Journal {
+    const VERSION: u32 = 1;
+
+    fn path(&self) -> PathBuf {
+        self.path.to_owned()
+    }
+}
+
+impl Journal {
+    pub fn load_path(path: &Path, prefix: &str) -> Result<Self, Error> {
+        let mut journal = match <Self as FileIO>::load_path(path.to_path_buf()) {
+            Ok(journal) => journal,
+            Err(Error::FileUnparsable(..)) => {
+                return Err(Error::JournalCorrupt(path.to_path_buf()))
+            }
+            Err(error) => return Err(error),
+        };
+        journal.path = path.to_path_buf();
+        journal.prefix = prefix.to_owned();
+
+        Ok(journal)
+    }
+
+    pub fn get_entries(&self) -> &Vec<JournalEntry> {
+        &self.entries
+    }
+
+    // @desc: Adds an entry for the operation, numbered after the entries of the journal as it is
+    //        on disk.
+    pub fn record(&mut self, operation: Operation) -> Result<(), Error> {
+        self.modify_entries(|journal| {
+            journal.entries.push(JournalEntry {
+                id: format!("{}{}", journal.prefix, journal.entries.len() + 1),
+                time: Local::now().format(TIME_FORMAT).to_string(),
+                operation,
+            });
+            Ok(())
+        })
+    }
+
+    // @desc: Finds the latest operation that hasn't been undone yet.
+    pub fn last_undoable(&self) -> Option<&JournalEntry> {
+        let undone: HashSet<&String> = self
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.operation {
+                Operation::Undo { id } => Some(id),
+                _ => None,
+            })
+            .collect();
+
+        self.entries.iter().rev().find(|entry| {
+            !matches!(entry.operation, Operation::Undo { .. }) && !undone.contains(&entry.id)
+        })
+    }
+
+    // @desc: Wraps FileIO::modify(), which reloads the file and so would drop the path and
+    //        prefix (as they aren't stored).
+    fn modify_entries<F>(&mut self, change: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        let (path, prefix) = (self.path.to_owned(), self.prefix.to_owned());
+
+        self.modify(|journal| {
+            journal.path = path;
+            journal.prefix = prefix;
+            change(journal)
+        })
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    if path.as_os_str().is_empty() {
+        "/".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
@@ -1,3 +1,6 @@
 pub mod args;
 pub mod config;
+pub mod editor;
+pub mod journal;
+pub mod trash;
 pub mod vaults;
@@ -0,0 +1,301 @@
+use crate::{enums::Item, output::error::Error, traits::FileIO};
+use chrono::{Duration, Local, NaiveDateTime};
+use fs_extra::{dir::CopyOptions, move_items};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    fs::{create_dir_all, remove_dir_all},
+    mem::take,
+    path::{Path, PathBuf},
+};
+
+// Format used for the time items were deleted at.
+const DELETED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// @desc: An item sitting in trash.
+//
+// @notes:
+//      -> "name" is the file name of the item, i.e. it includes the extension for notes.
+//      -> "origin" is the folder the item was removed from. It's relative to the root of the
+//         vault for notes and folders, and absolute for vaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    id: String,
+    item: Item,
+    name: String,
+    origin: PathBuf,
+    deleted: String,
+}
+
+impl TrashEntry {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_item(&self) -> &Item {
+        &self.item
+    }
+
+    // Name of the item as used in commands, i.e. without the extension for notes.
+    pub fn get_name(&self) -> String {
+        match self.item {
+            Item::Note | Item::Nt => Path::new(&self.name)
+                .with_extension("")
+                .to_string_lossy()
+                .to_string(),
+            _ => self.name.to_owned(),
+        }
+    }
+
+    pub fn get_origin(&self) -> &PathBuf {
+        &self.origin
+    }
+}
+
+impl Display for TrashEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = join_origin(&self.origin, &self.get_name());
+
+        write!(
+            f,
+            "\x1b[0;34m{}\x1b[0m \t {} \t {} \t {}",
+            self.id,
+            self.deleted,
+            self.item.full(),
+            path.display()
+        )
+    }
+}
+
+// @desc: Trash of a vault (in ".jot/trash") or the global trash for vaults (next to the data
+//        file).
+//
+// @notes:
+//      -> Items are kept in "files/<id>" inside the trash folder, and "index" keeps track of
+//         them.
+//      -> Ids of items in the global trash are prefixed with "v" so that they can be told apart
+//         from ids of the vault's trash.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Trash {
+    #[serde(skip)]
+    root: PathBuf,
+    #[serde(skip)]
+    prefix: String,
+    version: u32,
+    next_id: usize,
+    entries: Vec<TrashEntry>,
+}
+
+impl Default for Trash {
+    fn default() -> Self {
+        Trash {
+            root: PathBuf::new(),
+            prefix: String::new(),
+            version: Self::VERSION,
+            next_id: 0,
+            entries: vec![],
+        }
+    }
+}
+
+impl FileIO for Trash {
+    const VERSION: u32 = 1;
+
+    fn path(&self) -> PathBuf {
+        self.root.join("index")
+    }
+}
+
+impl Trash {
+    pub fn load_root(root: &Path, prefix: &str) -> Result<Self, Error> {
+        let mut trash = Trash::load_path(root.join("index"))?;
+        trash.root = root.to_path_buf();
+        trash.prefix = prefix.to_owned();
+        Ok(trash)
+    }
+
+    pub fn get_entries(&self) -> &Vec<TrashEntry> {
+        &self.entries
+    }
+
+    // @desc: Moves the item at path into trash, and returns the id it was given.
+    pub fn add(&mut self, item: Item, path: &Path, origin: PathBuf) -> Result<String, Error> {
+        let mut id = String::new();
+
+        self.modify_index(|trash| {
+            trash.next_id += 1;
+            id = format!("{}{}", trash.prefix, trash.next_id);
+
+            let files = trash.root.join("files").join(&id);
+            if let Err(error) = create_dir_all(&files) {
+                return Err(Error::Undefined(error));
+            }
+            move_items(&[path], &files, &CopyOptions::new())?;
+
+            trash.entries.push(TrashEntry {
+                id: id.to_owned(),
+                item,
+                name: path.file_name().unwrap().to_string_lossy().to_string(),
+                origin,
+                deleted: Local::now().format(DELETED_FORMAT).to_string(),
+            });
+            Ok(())
+        })?;
+
+        Ok(id)
+    }
+
+    // @desc: Moves an item out of trash, back into the origin folder (with base as the root for
+    //        relative origins). Returns the entry of the restored item.
+    pub fn restore(&mut self, id: &str, base: &Path) -> Result<TrashEntry, Error> {
+        let mut restored = None;
+
+        self.modify_index(|trash| {
+            let position = match trash.entries.iter().position(|entry| entry.id == id) {
+                Some(position) => position,
+                None => return Err(Error::TrashItemNotFound(id.to_owned())),
+            };
+            let entry = trash.entries[position].to_owned();
+
+            let location = join_origin(base, &entry.origin.to_string_lossy());
+            if location.join(&entry.name).exists() {
+                return Err(Error::ItemAlreadyExists(
+                    entry.item.to_owned(),
+                    entry.get_name(),
+                ));
+            }
+            if let Err(error) = create_dir_all(&location) {
+                return Err(Error::Undefined(error));
+            }
+
+            let files = trash.root.join("files").join(id);
+            move_items(&[files.join(&entry.name)], &location, &CopyOptions::new())?;
+            remove_dir_all(&files).ok();
+
+            trash.entries.remove(position);
+            restored = Some(entry);
+            Ok(())
+        })?;
+
+        Ok(restored.unwrap())
+    }
+
+    // @desc: Permanently deletes items from trash, either all of them or the ones deleted longer
+    //        than the given age ago. Returns the number of items deleted.
+    //
+    // @notes:
+    //      -> On a failure, items deleted before it are still removed from the index, while the
+    //         rest are kept.
+    pub fn empty(&mut self, older_than: Option<Duration>) -> Result<usize, Error> {
+        let now = Local::now().naive_local();
+        let mut deleted = 0;
+        let mut failure = None;
+
+        self.modify_index(|trash| {
+            for entry in take(&mut trash.entries) {
+                let expired = match older_than {
+                    Some(age) => NaiveDateTime::parse_from_str(&entry.deleted, DELETED_FORMAT)
+                        .is_ok_and(|deleted| now - deleted > age),
+                    None => true,
+                };
+
+                if !expired || failure.is_some() {
+                    trash.entries.push(entry);
+                    continue;
+                }
+
+                match remove_dir_all(trash.root.join("files").join(&entry.id)) {
+                    Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                        failure = Some(error);
+                        trash.entries.push(entry);
+                    }
+                    _ => deleted += 1,
+                }
+            }
+            Ok(())
+        })?;
+
+        match failure {
+            Some(error) => Err(Error::Undefined(error)),
+            None => Ok(deleted),
+        }
+    }
+
+    // @desc: Wraps FileIO::modify(), which reloads the index and so would drop the root and
+    //        prefix (as they aren't stored).
+    //
+    // @notes:
+    //      -> Items are moved while the index is locked, so that concurrent invocations can't
+    //         give out the same id.
+    fn modify_index<F>(&mut self, change: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        let (root, prefix) = (self.root.to_owned(), self.prefix.to_owned());
+
+        self.modify(|trash| {
+            trash.root = root;
+            trash.prefix = prefix;
+            change(trash)
+        })
+    }
+}
+
+// @desc: Parses an age like "30d", "12h" or "2w".
+//
+// @notes:
+//      -> Ages have to be positive, since e.g. "-5d" would otherwise match every item.
+pub fn parse_age(age: &str) -> Result<Duration, Error> {
+    let invalid = || Error::InvalidAge(age.to_owned());
+
+    let unit = age.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = age[..age.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+
+    if amount <= 0 {
+        return Err(invalid());
+    }
+
+    match unit {
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+fn join_origin(base: &Path, origin: &str) -> PathBuf {
+    if origin.is_empty() {
+        base.to_path_buf()
+    } else {
+        base.join(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_age_reads_hours_days_and_weeks() {
+        assert_eq!(parse_age("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_age("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_age("2w").unwrap(), Duration::weeks(2));
+    }
+
+    #[test]
+    fn parse_age_rejects_ages_that_are_not_positive() {
+        for age in ["-5d", "0d", "-1h", "+0w"] {
+            assert!(matches!(parse_age(age), Err(Error::InvalidAge(_))));
+        }
+    }
+
+    #[test]
+    fn parse_age_rejects_malformed_ages() {
+        for age in ["", "d", "5", "5y", "five days", "99999999999999w"] {
+            assert!(matches!(parse_age(age), Err(Error::InvalidAge(_))));
+        }
+    }
+}
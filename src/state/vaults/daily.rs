@@ -0,0 +1,194 @@
+use crate::output::error::Error;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, NaiveDate,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
+
+// @desc: Daily note settings of a vault, stored in its data file.
+//
+// @notes:
+//      -> "folder" is relative to the root of the vault.
+//      -> "format" is a chrono (strftime) format used to generate note names from dates.
+//      -> "template" is used when creating daily notes, falling back to the default template
+//         from config.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyConfig {
+    folder: PathBuf,
+    format: String,
+    template: Option<String>,
+}
+
+impl Default for DailyConfig {
+    fn default() -> Self {
+        DailyConfig {
+            folder: PathBuf::new(),
+            format: "%Y-%m-%d".to_string(),
+            template: None,
+        }
+    }
+}
+
+impl DailyConfig {
+    pub fn get_folder(&self) -> &PathBuf {
+        &self.folder
+    }
+
+    // @desc: Sets the folder daily notes are kept in, which has to be inside the vault.
+    //
+    // @notes:
+    //      -> The folder doesn't have to exist yet, so it's normalized without touching the file
+    //         system. Absolute paths and paths climbing out of the vault's root are out of bounds.
+    pub fn set_folder(&mut self, folder: &str) -> Result<(), Error> {
+        let mut normalized = PathBuf::new();
+
+        for component in Path::new(folder).components() {
+            match component {
+                Component::Normal(name) => normalized.push(name),
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    if !normalized.pop() {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(Error::OutOfBounds),
+            }
+        }
+
+        self.folder = normalized;
+        Ok(())
+    }
+
+    pub fn get_format(&self) -> &String {
+        &self.format
+    }
+
+    pub fn set_format(&mut self, format: String) -> Result<(), Error> {
+        if format.is_empty() || StrftimeItems::new(&format).any(|item| matches!(item, Item::Error))
+        {
+            return Err(Error::InvalidDateFormat(format));
+        }
+
+        self.format = format;
+        Ok(())
+    }
+
+    pub fn get_template(&self) -> Option<&String> {
+        self.template.as_ref()
+    }
+
+    // An empty value unsets the template.
+    pub fn set_template(&mut self, template: String) {
+        self.template = if template.is_empty() {
+            None
+        } else {
+            Some(template)
+        };
+    }
+
+    pub fn note_name(&self, date: &NaiveDate) -> String {
+        date.format(&self.format).to_string()
+    }
+}
+
+// @desc: Days of a month, some of which have daily notes.
+#[derive(Debug, Serialize)]
+pub struct DailyCalendar {
+    #[serde(serialize_with = "serialize_month")]
+    month: NaiveDate,
+    days: Vec<u32>,
+}
+
+impl DailyCalendar {
+    pub fn new(month: NaiveDate, days: Vec<u32>) -> Self {
+        DailyCalendar { month, days }
+    }
+}
+
+// Prints the month as a calendar with weeks starting on monday, days with notes highlighted.
+impl Display for DailyCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = vec![
+            self.month.format("%B %Y").to_string(),
+            "Mo Tu We Th Fr Sa Su".to_string(),
+        ];
+
+        let offset = self.month.weekday().num_days_from_monday() as usize;
+        let mut line = "   ".repeat(offset);
+
+        for day in 1..=days_in_month(&self.month) {
+            if self.days.contains(&day) {
+                line.push_str(&format!("\x1b[0;34m{day:>2}\x1b[0m "));
+            } else {
+                line.push_str(&format!("{day:>2} "));
+            }
+
+            if (offset + day as usize).is_multiple_of(7) {
+                lines.push(line.trim_end().to_string());
+                line = String::new();
+            }
+        }
+
+        if !line.is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+pub fn parse_date(date: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| Error::InvalidDate(date.to_owned()))
+}
+
+// @desc: Parses a month written as "YYYY-MM" into its first day.
+pub fn parse_month(month: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::InvalidDate(month.to_owned()))
+}
+
+// Months are serialized like they're passed to "jt daily -l", e.g. "2026-10".
+fn serialize_month<S: Serializer>(month: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&month.format("%Y-%m").to_string())
+}
+
+pub fn days_in_month(month: &NaiveDate) -> u32 {
+    let first = month.with_day(1).unwrap();
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+
+    next.signed_duration_since(first).num_days() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_folder_normalizes_paths_inside_the_vault() {
+        let mut daily = DailyConfig::default();
+
+        daily.set_folder("journal/./daily/../2026").unwrap();
+        assert_eq!(daily.get_folder(), &PathBuf::from("journal/2026"));
+
+        daily.set_folder("").unwrap();
+        assert_eq!(daily.get_folder(), &PathBuf::new());
+    }
+
+    #[test]
+    fn set_folder_rejects_paths_outside_the_vault() {
+        let mut daily = DailyConfig::default();
+        daily.set_folder("journal").unwrap();
+
+        for folder in ["../escaped", "journal/../../escaped", "/tmp/x"] {
+            assert!(matches!(daily.set_folder(folder), Err(Error::OutOfBounds)));
+        }
+        assert_eq!(daily.get_folder(), &PathBuf::from("journal"));
+    }
+}
@@ -1,15 +1,28 @@
-use crate::traits::FileIO;
+use crate::{output::error::Error, traits::FileIO};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Data {
+    version: u32,
     current: Option<String>,
     vaults: HashMap<String, PathBuf>,
 }
 
+impl Default for Data {
+    fn default() -> Self {
+        Data {
+            version: Self::VERSION,
+            current: None,
+            vaults: HashMap::new(),
+        }
+    }
+}
+
 impl FileIO for Data {
+    const VERSION: u32 = 1;
+
     fn path(&self) -> PathBuf {
         if let Some(dirs) = ProjectDirs::from("com", "", "jot") {
             let mut path = dirs.data_dir().to_path_buf();
@@ -26,9 +39,11 @@ impl Data {
         self.current.as_ref()
     }
 
-    pub fn set_current_vault(&mut self, vault: Option<String>) {
-        self.current = vault;
-        self.store()
+    pub fn set_current_vault(&mut self, vault: Option<String>) -> Result<(), Error> {
+        self.modify(|data| {
+            data.current = vault;
+            Ok(())
+        })
     }
 
     pub fn get_vaults(&self) -> &HashMap<String, PathBuf> {
@@ -39,28 +54,46 @@ impl Data {
         self.vaults.get(name)
     }
 
+    // Vaults are trashed next to the data file rather than inside another vault.
+    pub fn get_trash_dir(&self) -> PathBuf {
+        self.path().with_file_name("trash")
+    }
+
+    pub fn get_journal_path(&self) -> PathBuf {
+        self.path().with_file_name("journal")
+    }
+
     pub fn vault_exists(&self, name: &str) -> bool {
         self.vaults.contains_key(name)
     }
 
-    pub fn add_vault(&mut self, name: String, location: PathBuf) {
-        self.vaults.insert(name, location);
-        self.store()
+    pub fn add_vault(&mut self, name: String, location: PathBuf) -> Result<(), Error> {
+        self.modify(|data| {
+            data.vaults.insert(name, location);
+            Ok(())
+        })
     }
 
-    pub fn remove_vault(&mut self, name: &str) {
-        self.vaults.remove(name);
-        self.store()
+    pub fn remove_vault(&mut self, name: &str) -> Result<(), Error> {
+        self.modify(|data| {
+            data.vaults.remove(name);
+            Ok(())
+        })
     }
 
-    pub fn rename_vault(&mut self, name: &str, new_name: String) {
-        let value = self.vaults.remove(name);
-        self.vaults.insert(new_name, value.unwrap());
-        self.store()
+    pub fn rename_vault(&mut self, name: &str, new_name: String) -> Result<(), Error> {
+        self.modify(|data| {
+            if let Some(location) = data.vaults.remove(name) {
+                data.vaults.insert(new_name, location);
+            }
+            Ok(())
+        })
     }
 
-    pub fn set_vault_location(&mut self, name: &str, new_location: PathBuf) {
-        self.vaults.insert(name.to_owned(), new_location);
-        self.store()
+    pub fn set_vault_location(&mut self, name: &str, new_location: PathBuf) -> Result<(), Error> {
+        self.modify(|data| {
+            data.vaults.insert(name.to_owned(), new_location);
+            Ok(())
+        })
     }
 }
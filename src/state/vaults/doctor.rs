@@ -0,0 +1,172 @@
+use crate::output::{error::Error, strip_ansi};
+use serde::Serialize;
+use std::{fmt::Display, path::PathBuf};
+
+// @desc: An inconsistency between the registry of vaults and the vaults' own data, as found by
+//        "jt doctor".
+//
+// @notes:
+//      -> Paths of vaults are the ones in the registry, "stored_path" of MismatchedData is the
+//         one in the vault's data.
+//      -> "folder" of MissingFolder is relative to the root of the vault.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    UnknownCurrentVault {
+        vault: String,
+    },
+    MissingVault {
+        vault: String,
+        path: PathBuf,
+    },
+    MissingData {
+        vault: String,
+        path: PathBuf,
+    },
+    UnparsableData {
+        vault: String,
+        path: PathBuf,
+        error: String,
+    },
+    UnreadableData {
+        vault: String,
+        path: PathBuf,
+        error: String,
+    },
+    MismatchedData {
+        vault: String,
+        path: PathBuf,
+        stored_path: Option<PathBuf>,
+    },
+    MissingFolder {
+        vault: String,
+        folder: PathBuf,
+    },
+}
+
+impl Problem {
+    // @desc: Describes how the problem is fixed, if it can be.
+    pub fn fix(&self) -> Option<&'static str> {
+        match self {
+            Problem::UnknownCurrentVault { .. } => Some("leave the vault"),
+            Problem::MissingVault { .. } => Some("forget the vault"),
+            Problem::MissingData { .. } => Some("regenerate its data"),
+            Problem::UnparsableData { .. } => Some("back up its data and regenerate it"),
+            Problem::UnreadableData { .. } => None,
+            Problem::MismatchedData { .. } => Some("relink its data to the registered folder"),
+            Problem::MissingFolder { .. } => Some("reset its folder to the root of the vault"),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::UnknownCurrentVault { vault } => {
+                write!(f, "current vault \x1b[0;34m{vault}\x1b[0m isn't registered")
+            }
+            Problem::MissingVault { vault, path } => write!(
+                f,
+                "folder of vault \x1b[0;34m{vault}\x1b[0m doesn't exist at {} (if it was moved, run 'jt vault scan' on its new location instead)",
+                path.display()
+            ),
+            Problem::MissingData { vault, path } => write!(
+                f,
+                "vault \x1b[0;34m{vault}\x1b[0m has no data file at {}",
+                path.join(".jot/data").display()
+            ),
+            Problem::UnparsableData { vault, error, .. }
+            | Problem::UnreadableData { vault, error, .. } => {
+                write!(f, "data of vault \x1b[0;34m{vault}\x1b[0m: {error}")
+            }
+            Problem::MismatchedData {
+                vault,
+                path,
+                stored_path,
+            } => match stored_path {
+                Some(stored_path) => write!(
+                    f,
+                    "vault \x1b[0;34m{vault}\x1b[0m is registered at {}, but its data says {}",
+                    path.display(),
+                    stored_path.display()
+                ),
+                None => write!(
+                    f,
+                    "data of vault \x1b[0;34m{vault}\x1b[0m doesn't say where the vault is"
+                ),
+            },
+            Problem::MissingFolder { vault, folder } => write!(
+                f,
+                "current folder {} of vault \x1b[0;34m{vault}\x1b[0m doesn't exist",
+                folder.display()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    #[serde(flatten)]
+    problem: Problem,
+    fix: Option<&'static str>,
+    fixed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix_error: Option<String>,
+}
+
+impl Finding {
+    pub fn new(problem: Problem) -> Self {
+        Finding {
+            fix: problem.fix(),
+            problem,
+            fixed: false,
+            fix_error: None,
+        }
+    }
+
+    pub fn set_result(&mut self, result: Result<(), Error>) {
+        match result {
+            Ok(()) => self.fixed = true,
+            Err(error) => self.fix_error = Some(strip_ansi(&error.to_string())),
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\x1b[0;31m✗\x1b[0m {}", self.problem)?;
+
+        match (self.fix, self.fixed, &self.fix_error) {
+            (Some(fix), true, _) => write!(f, "\n  fixed: {fix}"),
+            (Some(fix), false, Some(error)) => write!(f, "\n  couldn't {fix}: {error}"),
+            (Some(fix), false, None) => write!(f, "\n  fix: {fix} (run 'jt doctor --fix')"),
+            (None, ..) => write!(f, "\n  no automatic fix"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    findings: Vec<Finding>,
+}
+
+impl DoctorReport {
+    pub fn new(findings: Vec<Finding>) -> Self {
+        DoctorReport { findings }
+    }
+}
+
+impl Display for DoctorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.findings.is_empty() {
+            return write!(f, "no problems found");
+        }
+
+        let lines: Vec<String> = self
+            .findings
+            .iter()
+            .map(|finding| finding.to_string())
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
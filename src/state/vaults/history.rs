@@ -0,0 +1,75 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::PathBuf};
+
+// Number of entries kept in a vault's history.
+const HISTORY_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryKind {
+    Folder,
+    Note,
+}
+
+// @desc: A folder that was left or a note that was opened.
+//
+// @notes:
+//      -> "path" is relative to the root of the vault, and includes the extension for notes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    kind: HistoryKind,
+    path: PathBuf,
+    time: String,
+}
+
+impl HistoryEntry {
+    pub fn get_kind(&self) -> &HistoryKind {
+        &self.kind
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (kind, path) = match self.kind {
+            HistoryKind::Folder if self.path.as_os_str().is_empty() => ("folder", "/".to_string()),
+            HistoryKind::Folder => ("folder", self.path.display().to_string()),
+            HistoryKind::Note => ("note", self.path.with_extension("").display().to_string()),
+        };
+
+        write!(f, "{} \t {kind} \t {path}", self.time)
+    }
+}
+
+// @desc: Adds an entry to the front of history, dropping the oldest entries past the limit.
+//
+// @notes:
+//      -> An earlier entry for the same item is removed, so every item shows up once.
+pub fn push_history(history: &mut Vec<HistoryEntry>, kind: HistoryKind, path: PathBuf) {
+    history.retain(|entry| entry.kind != kind || entry.path != path);
+
+    history.insert(
+        0,
+        HistoryEntry {
+            kind,
+            path,
+            time: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        },
+    );
+    history.truncate(HISTORY_LIMIT);
+}
+
+// @desc: Parses references to history entries, written as "@<number>" (starting at 1 for the
+//        most recent entry). Returns the index of the entry.
+pub fn parse_history_ref(reference: &str) -> Option<usize> {
+    reference
+        .strip_prefix('@')?
+        .parse::<usize>()
+        .ok()
+        .filter(|number| *number > 0)
+        .map(|number| number - 1)
+}
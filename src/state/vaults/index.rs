@@ -0,0 +1,284 @@
+use crate::{
+    notes::types::NoteTypes,
+    output::{error::Error, warn},
+    traits::FileIO,
+    utils::walk_notes,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::read_to_string,
+    io::ErrorKind,
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+    time::UNIX_EPOCH,
+};
+
+// @desc: Inverted index of the notes in a vault, stored in ".jot/index" next to the vault's data
+//        file.
+//
+// @notes:
+//      -> All paths are relative to the root of the vault.
+//      -> "files" keeps the modification time (in milliseconds) each note had when it was last
+//         indexed, which is how edits made outside of jot are picked up.
+//      -> "terms" maps every lowercased word to the notes it appears in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Index {
+    #[serde(skip)]
+    root: PathBuf,
+    #[serde(skip)]
+    note_types: NoteTypes,
+    version: u32,
+    files: BTreeMap<String, u64>,
+    terms: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index {
+            root: PathBuf::new(),
+            note_types: NoteTypes::default(),
+            version: Self::VERSION,
+            files: BTreeMap::new(),
+            terms: BTreeMap::new(),
+        }
+    }
+}
+
+impl FileIO for Index {
+    const VERSION: u32 = 1;
+
+    fn path(&self) -> PathBuf {
+        self.root.join(".jot/index")
+    }
+}
+
+impl Index {
+    pub fn new(root: &Path, note_types: NoteTypes) -> Self {
+        Index {
+            root: root.to_path_buf(),
+            note_types,
+            ..Default::default()
+        }
+    }
+
+    // @desc: Loads the index of the vault at root, builds it if it doesn't exist yet.
+    //
+    // @notes:
+    //      -> Unlike FileIO::load_path(), a file that can't be parsed (or isn't valid UTF-8) is
+    //         reported as an error so that it can be fixed with "jt index rebuild".
+    pub fn load_root(root: &Path, note_types: NoteTypes) -> Result<Self, Error> {
+        let mut index = Index::new(root, note_types);
+
+        let path = index.path();
+        match read_to_string(&path) {
+            Ok(file_string) => {
+                // Indexes of older versions are migrated in memory, and stored with the next
+                // update.
+                if let Ok((file_data, _)) = Index::parse(&path, &file_string) {
+                    index.files = file_data.files;
+                    index.terms = file_data.terms;
+                } else {
+                    return Err(Error::IndexCorrupt);
+                }
+            }
+            Err(ref error) if error.kind() == ErrorKind::NotFound => {
+                index.rebuild()?;
+            }
+            Err(ref error) if error.kind() == ErrorKind::InvalidData => {
+                return Err(Error::IndexCorrupt);
+            }
+            Err(error) => return Err(Error::Undefined(error)),
+        }
+
+        Ok(index)
+    }
+
+    // @desc: Drops everything and indexes all notes in the vault again. Returns the number of
+    //        notes indexed.
+    pub fn rebuild(&mut self) -> Result<usize, Error> {
+        self.files.clear();
+        self.terms.clear();
+
+        for note in walk_notes(&self.root, &self.note_types)? {
+            self.add_note(&note);
+        }
+
+        self.store()?;
+        Ok(self.files.len())
+    }
+
+    // @desc: Brings the index up to date with the notes on disk by comparing modification times.
+    //        Notes that were added, edited or removed outside of jot are handled here.
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        let mut changed = false;
+        let notes = walk_notes(&self.root, &self.note_types)?;
+
+        let mut stale: BTreeSet<String> = self.files.keys().cloned().collect();
+
+        for note in notes {
+            let key = self.key(&note);
+            stale.remove(&key);
+
+            if self.files.get(&key) != Some(&modified_time(&note)) {
+                self.add_note(&note);
+                changed = true;
+            }
+        }
+
+        for key in stale {
+            self.remove_key(&key);
+            changed = true;
+        }
+
+        if changed {
+            self.store()?;
+        }
+
+        Ok(())
+    }
+
+    // @desc: Indexes (or re-indexes) the note at path and stores the index.
+    pub fn update_note(&mut self, path: &Path) -> Result<(), Error> {
+        self.add_note(path);
+        self.store()
+    }
+
+    // @desc: Removes the note at path, or every note inside the folder at path, and stores the
+    //        index.
+    pub fn remove_path(&mut self, path: &Path) -> Result<(), Error> {
+        let prefix = self.key(path);
+
+        for key in self.keys_under(&prefix) {
+            self.remove_key(&key);
+        }
+
+        self.store()
+    }
+
+    // @desc: Moves the entries of the note (or every note inside the folder) at path to
+    //        new_path without reading the notes again, then stores the index.
+    pub fn rename_path(&mut self, path: &Path, new_path: &Path) -> Result<(), Error> {
+        let prefix = self.key(path);
+        let new_prefix = self.key(new_path);
+
+        for key in self.keys_under(&prefix) {
+            let new_key = format!("{new_prefix}{}", &key[prefix.len()..]);
+
+            if let Some(modified) = self.files.remove(&key) {
+                self.files.insert(new_key.clone(), modified);
+            }
+
+            for files in self.terms.values_mut() {
+                if files.remove(&key) {
+                    files.insert(new_key.clone());
+                }
+            }
+        }
+
+        self.store()
+    }
+
+    // @desc: Returns paths to the notes that might match query, i.e. notes that contain, for
+    //        every word in query, a term that contains that word.
+    //
+    // @notes:
+    //      -> Queries without any words can't be narrowed down, so all notes are returned.
+    pub fn candidates(&self, query: &str) -> Vec<PathBuf> {
+        let words = tokenize(query);
+
+        if words.is_empty() {
+            return self.notes();
+        }
+
+        let mut candidates: Option<BTreeSet<&String>> = None;
+
+        for word in words {
+            let matching: BTreeSet<&String> = self
+                .terms
+                .iter()
+                .filter(|(term, _)| term.contains(&word))
+                .flat_map(|(_, files)| files)
+                .collect();
+
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&matching).cloned().collect(),
+                None => matching,
+            });
+        }
+
+        candidates
+            .unwrap_or_default()
+            .into_iter()
+            .map(|key| self.root.join(key))
+            .collect()
+    }
+
+    pub fn notes(&self) -> Vec<PathBuf> {
+        self.files.keys().map(|key| self.root.join(key)).collect()
+    }
+
+    // @desc: Indexes the note at path, replacing whatever was indexed for it before.
+    //
+    // @notes:
+    //      -> Notes that can't be read (e.g. ones that aren't valid UTF-8) are left out of the index
+    //         with a warning, so one bad file doesn't keep the rest of the vault from being
+    //         searched. They're tried again on every refresh.
+    fn add_note(&mut self, path: &Path) {
+        let key = self.key(path);
+        self.remove_key(&key);
+
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(error) => {
+                warn(&format!("skipped {key}: {error}"));
+                return;
+            }
+        };
+
+        for term in tokenize(&content) {
+            self.terms.entry(term).or_default().insert(key.clone());
+        }
+        self.files.insert(key, modified_time(path));
+    }
+
+    fn remove_key(&mut self, key: &str) {
+        self.files.remove(key);
+
+        self.terms.retain(|_, files| {
+            files.remove(key);
+            !files.is_empty()
+        });
+    }
+
+    // Collects keys of the note at prefix or of the notes inside the folder at prefix.
+    fn keys_under(&self, prefix: &str) -> Vec<String> {
+        self.files
+            .keys()
+            .filter(|key| *key == prefix || key.starts_with(&format!("{prefix}{MAIN_SEPARATOR}")))
+            .cloned()
+            .collect()
+    }
+
+    fn key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+fn tokenize(text: &str) -> BTreeSet<String> {
+    text.split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn modified_time(path: &Path) -> u64 {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,225 @@
+use crate::{
+    enums::VaultItem,
+    notes::types::NoteTypes,
+    utils::{contains_notes, list_entries, NoteFilter},
+};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+// @desc: A vault as listed by "jt vault".
+#[derive(Debug, Serialize)]
+pub struct VaultListing {
+    name: String,
+    location: PathBuf,
+    current: bool,
+}
+
+impl VaultListing {
+    pub fn new(name: String, location: PathBuf, current: bool) -> Self {
+        VaultListing {
+            name,
+            location,
+            current,
+        }
+    }
+}
+
+// @desc: Registered vaults, sorted by name.
+//
+// @notes:
+//      -> Locations are only displayed when "show_loc" is set, but always serialized.
+#[derive(Debug, Serialize)]
+pub struct VaultList {
+    #[serde(skip)]
+    show_loc: bool,
+    vaults: Vec<VaultListing>,
+}
+
+impl VaultList {
+    pub fn new(mut vaults: Vec<VaultListing>, show_loc: bool) -> Self {
+        vaults.sort_by(|a, b| a.name.cmp(&b.name));
+        VaultList { show_loc, vaults }
+    }
+}
+
+impl Display for VaultList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.vaults.is_empty() {
+            return write!(f, "no vaults found");
+        }
+
+        let lines: Vec<String> = self
+            .vaults
+            .iter()
+            .map(|vault| {
+                let mut line = if vault.current {
+                    format!("👉 \x1b[0;34m{}\x1b[0m", vault.name)
+                } else {
+                    format!("   {}", vault.name)
+                };
+
+                if self.show_loc {
+                    line.push_str(&format!(" \t {}", vault.location.display()));
+                }
+
+                line
+            })
+            .collect();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TreeEntryKind {
+    Folder,
+    Note,
+}
+
+// @desc: A note or folder in a folder tree. Names of notes don't include the extension, unless
+//        it's another one than the default.
+#[derive(Debug, Serialize)]
+pub struct TreeEntry {
+    name: String,
+    #[serde(rename = "type")]
+    kind: TreeEntryKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<TreeEntry>>,
+}
+
+impl TreeEntry {
+    fn new(path: &Path, children: Option<Vec<TreeEntry>>, note_types: &NoteTypes) -> Self {
+        let is_dir = path.is_dir();
+
+        TreeEntry {
+            name: if is_dir {
+                path.file_name().unwrap().to_string_lossy().to_string()
+            } else {
+                note_types.display_name(path)
+            },
+            kind: if is_dir {
+                TreeEntryKind::Folder
+            } else {
+                TreeEntryKind::Note
+            },
+            children,
+        }
+    }
+
+    fn is_note(&self) -> bool {
+        matches!(self.kind, TreeEntryKind::Note)
+    }
+}
+
+// @desc: Items of the current folder, as listed by "jt list".
+//
+// @notes:
+//      -> When an item type is passed, only the items of that type directly inside the folder
+//         are listed, otherwise the whole tree under it is.
+//      -> When a filter is passed, only the notes that pass it and the folders that contain them
+//         are listed.
+#[derive(Debug, Serialize)]
+pub struct FolderTree {
+    vault: String,
+    folder: PathBuf,
+    entries: Vec<TreeEntry>,
+}
+
+impl FolderTree {
+    pub fn build(
+        vault: &str,
+        folder: &Path,
+        location: &Path,
+        item_type: &Option<VaultItem>,
+        filter: NoteFilter,
+        note_types: &NoteTypes,
+    ) -> Self {
+        let entries = match item_type {
+            Some(item_type) => list_entries(location, filter, note_types)
+                .iter()
+                .filter(|entry| match item_type {
+                    VaultItem::Folder | VaultItem::Fd => {
+                        entry.is_dir()
+                            && (filter.is_none() || contains_notes(entry, filter, note_types))
+                    }
+                    _ => entry.is_file(),
+                })
+                .map(|entry| TreeEntry::new(entry, None, note_types))
+                .collect(),
+            None => build_tree(location, filter, note_types),
+        };
+
+        FolderTree {
+            vault: vault.to_owned(),
+            folder: folder.to_path_buf(),
+            entries,
+        }
+    }
+}
+
+impl Display for FolderTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.folder.as_os_str().is_empty() {
+            write!(f, "{}", self.vault)?;
+        } else {
+            write!(f, "{} > {}", self.vault, self.folder.display())?;
+        }
+
+        write_tree(f, &self.entries, "")
+    }
+}
+
+// @desc: Recursively builds the tree of entries under a folder.
+//
+// @notes:
+//      -> With a filter, folders are left out once their own children are built and turn out
+//         empty, so that the tree is walked (and every note read) only once.
+fn build_tree(path: &Path, filter: NoteFilter, note_types: &NoteTypes) -> Vec<TreeEntry> {
+    list_entries(path, filter, note_types)
+        .iter()
+        .filter_map(|entry| {
+            if entry.is_dir() {
+                let children = build_tree(entry, filter, note_types);
+                if filter.is_some() && children.is_empty() {
+                    return None;
+                }
+                Some(TreeEntry::new(entry, Some(children), note_types))
+            } else {
+                Some(TreeEntry::new(entry, None, note_types))
+            }
+        })
+        .collect()
+}
+
+// @desc: Writes entries as a tree, one per line.
+//
+// @notes:
+//      -> "prefix" holds the pipes of the levels above, i.e. a pipe for every parent folder that
+//         isn't the last element in its own parent folder.
+fn write_tree(
+    f: &mut std::fmt::Formatter<'_>,
+    entries: &[TreeEntry],
+    prefix: &str,
+) -> std::fmt::Result {
+    for (count, entry) in entries.iter().enumerate() {
+        let is_last = entries.len() - count == 1;
+        let branch = if is_last { "└── " } else { "├── " };
+
+        if entry.is_note() {
+            write!(f, "\n{prefix}{branch}\x1b[0;34m{}\x1b[0m", entry.name)?;
+        } else {
+            write!(f, "\n{prefix}{branch}{}", entry.name)?;
+        }
+
+        if let Some(children) = &entry.children {
+            let prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+            write_tree(f, children, &prefix)?;
+        }
+    }
+
+    Ok(())
+}
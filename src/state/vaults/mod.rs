@@ -1,72 +1,147 @@
+pub mod daily;
 pub mod data;
+pub mod doctor;
+pub mod history;
+pub mod index;
+pub mod listing;
+pub mod scan;
 pub mod vault;
 
 use crate::{
     enums::{Item, VaultItem},
-    output::error::Error,
-    traits::FileIO,
-    utils::{create_item, join_paths, move_item, remove_item, rename_item, resolve_path},
+    notes::types::{NoteTypes, NoteTypesConfig},
+    output::{error::Error, strip_ansi},
+    state::{
+        config::Config,
+        journal::{Journal, JournalEntry, Operation},
+        trash::{Trash, TrashEntry},
+    },
+    traits::{Confirm, FileIO},
+    utils::{create_item, generate_item_path, join_paths, move_item, rename_item, resolve_path},
 };
+use chrono::Duration;
 use data::Data;
-use std::path::Path;
+use doctor::{DoctorReport, Finding, Problem};
+use listing::{VaultList, VaultListing};
+use scan::{find_vaults, ScanReport, ScanStatus, ScannedVault};
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
 use vault::Vault;
 
+// Names of the "jt vault" subcommands. Vaults can't be named after them, since "jt vault <vault
+// name> <vault location>" would be read as the subcommand.
+const RESERVED_VAULT_NAMES: [&str; 3] = ["add", "forget", "scan"];
+
 #[derive(Debug)]
 pub struct Vaults {
     current: Option<Vault>,
+    // Name of the vault passed for this invocation, if there is one.
+    selected: Option<String>,
+    // Global note types from config, which vaults fall back to.
+    note_types: NoteTypesConfig,
+    // Why the vault commands work in couldn't be loaded, if its data file is unparsable or too
+    // new. Only reported by commands that need the vault.
+    load_error: Option<Error>,
     data: Data,
 }
 
 impl Vaults {
-    pub fn load() -> Self {
-        let mut vaults = Vaults {
-            current: None,
-            data: Data::load(),
+    // @desc: Loads the registry of vaults along with the vault commands work in, which is the
+    //        passed one if there is one, or else the current one.
+    //
+    // @notes:
+    //      -> A passed vault is used for this invocation only, the current vault stays as it is.
+    //      -> A passed vault that can't be found, or whose data file can't be read, is only
+    //         reported by commands that need it, so that e.g. vaults can still be listed.
+    pub fn load(
+        confirm: Confirm,
+        vault_name: Option<&str>,
+        note_types: &NoteTypesConfig,
+    ) -> Result<Self, Error> {
+        let mut vaults = Vaults::load_registry(confirm, note_types)?;
+        vaults.selected = vault_name.map(|vault_name| vault_name.to_owned());
+
+        let current = match vault_name {
+            Some(vault_name) => vaults.load_vault(vault_name, confirm),
+            None => match vaults.data.get_current_vault() {
+                Some(current_vault_name) => vaults.load_vault(current_vault_name, confirm),
+                None => Ok(None),
+            },
         };
-        vaults.load_current_vault();
-        vaults
+
+        match current {
+            Ok(current) => vaults.current = current,
+            Err(error @ (Error::FileUnparsable(..) | Error::UnsupportedVersion(..))) => {
+                vaults.load_error = Some(error)
+            }
+            Err(error) => return Err(error),
+        }
+
+        Ok(vaults)
     }
 
-    fn load_current_vault(&mut self) {
-        self.current = if let Some(current_vault_name) = self.data.get_current_vault() {
-            let current_vault_location = self.data.get_vault_location(current_vault_name).unwrap();
+    // @desc: Loads the registry of vaults alone, for commands that don't work in a vault.
+    //
+    // @notes:
+    //      -> Used by "jt doctor", which has to run even when the current vault's data is broken,
+    //         without offering to regenerate it before the problem is reported.
+    pub fn load_registry(confirm: Confirm, note_types: &NoteTypesConfig) -> Result<Self, Error> {
+        Ok(Vaults {
+            current: None,
+            selected: None,
+            note_types: note_types.to_owned(),
+            load_error: None,
+            data: Data::load(confirm)?,
+        })
+    }
 
-            let path = join_paths(vec![
-                current_vault_location.to_str().unwrap(),
-                current_vault_name,
-                ".jot/data",
-            ]);
+    // @desc: Loads any registered vault.
+    //
+    // @notes:
+    //      -> Nothing is returned for a vault that isn't registered or whose folder no longer
+    //         exists, so that commands which don't need it keep working.
+    //      -> A regenerated data file doesn't know the name and location of its vault, so they're
+    //         restored from the data file of vaults.
+    pub fn load_vault(&self, name: &str, confirm: Confirm) -> Result<Option<Vault>, Error> {
+        let Some(location) = self.data.get_vault_location(name) else {
+            return Ok(None);
+        };
 
-            Some(Vault::load_path(path))
-        } else {
-            None
+        let vault_path = join_paths(vec![location.to_str().unwrap(), name]);
+        if !vault_path.is_dir() {
+            return Ok(None);
+        }
+
+        let mut vault = Vault::load_or_recover(vault_path.join(".jot/data"), confirm)?;
+        if !vault.has_path_data() {
+            vault.set_path_data(name.to_owned(), location.to_owned())?;
         }
+        vault.set_global_note_types(&self.note_types);
+
+        Ok(Some(vault))
     }
 
-    pub fn list_vaults(&self, show_loc: &bool) {
+    pub fn list_vaults(&self, show_loc: &bool) -> VaultList {
         let current_vault_name = self.data.get_current_vault();
 
-        for vault_name in self.data.get_vaults().keys() {
-            if current_vault_name.is_some() && vault_name == current_vault_name.unwrap() {
-                print!("👉 \x1b[0;34m{vault_name}\x1b[0m")
-            } else {
-                print!("   {vault_name}")
-            }
-
-            if *show_loc {
-                println!(
-                    " \t {}",
-                    self.data.get_vault_location(vault_name).unwrap().display()
-                )
-            } else {
-                println!();
-            }
-        }
+        let vaults = self
+            .data
+            .get_vaults()
+            .iter()
+            .map(|(name, location)| {
+                let current = current_vault_name == Some(name);
+                VaultListing::new(name.to_owned(), location.to_owned(), current)
+            })
+            .collect();
+
+        VaultList::new(vaults, *show_loc)
     }
 
     pub fn ref_current(&self) -> Result<&Vault, Error> {
         if self.current.is_none() {
-            return Err(Error::NotInsideVault);
+            return Err(self.no_current_error());
         }
 
         Ok(self.current.as_ref().unwrap())
@@ -74,13 +149,28 @@ impl Vaults {
 
     pub fn mut_current(&mut self) -> Result<&mut Vault, Error> {
         if self.current.is_none() {
-            return Err(Error::NotInsideVault);
+            return Err(self.no_current_error());
         }
 
         Ok(self.current.as_mut().unwrap())
     }
 
+    fn no_current_error(&self) -> Error {
+        match (&self.load_error, &self.selected) {
+            (Some(Error::FileUnparsable(path, line)), _) => {
+                Error::FileUnparsable(path.to_owned(), *line)
+            }
+            (Some(Error::UnsupportedVersion(path, version)), _) => {
+                Error::UnsupportedVersion(path.to_owned(), *version)
+            }
+            (_, Some(vault_name)) => Error::VaultNotFound(vault_name.to_owned()),
+            (_, None) => Error::NotInsideVault,
+        }
+    }
+
     pub fn create_vault(&mut self, name: &str, location: &Path) -> Result<(), Error> {
+        check_vault_name(name)?;
+
         if self.data.vault_exists(name) {
             return Err(Error::VaultAlreadyExists(name.to_owned()));
         }
@@ -90,51 +180,571 @@ impl Vaults {
         }
 
         let location = resolve_path(location)?;
-        let path = create_item(Item::Vl, name, &location)?;
+        let path = create_item(Item::Vl, name, &location, &NoteTypes::default())?;
         let data_path = join_paths(vec![path.to_str().unwrap(), ".jot/data"]);
 
-        let mut vault = Vault::load_path(data_path);
-        vault.set_name(name.to_owned());
-        vault.set_location(location.to_owned());
-        vault.store();
+        Vault::load_path(data_path)?.set_path_data(name.to_owned(), location.to_owned())?;
+
+        self.data.add_vault(name.to_owned(), location)?;
+
+        self.record(Operation::Create {
+            item: Item::Vl,
+            path,
+        });
+        Ok(())
+    }
+
+    // @desc: Registers an existing folder as a vault, without touching its contents.
+    //
+    // @notes:
+    //      -> Vaults are named after their folders, so the last component of the path has to be
+    //         the name of the vault.
+    //      -> A folder that already has vault data (e.g. of a vault that was forgotten) keeps it,
+    //         only its name and location are updated.
+    pub fn add_vault(&mut self, name: &str, path: &Path) -> Result<(), Error> {
+        if !path.is_absolute() {
+            return Err(Error::PathNotAbsolute);
+        }
+
+        let path = resolve_path(path)?;
+        if !path.is_dir() {
+            return Err(Error::PathNotFound);
+        }
+
+        let location = match path.parent() {
+            Some(location) => location.to_path_buf(),
+            None => return Err(Error::FolderNameMismatch(name.to_owned())),
+        };
+        if generate_item_path(&Item::Vl, name, &location, &NoteTypes::default())? != path {
+            return Err(Error::FolderNameMismatch(name.to_owned()));
+        }
+
+        self.register_vault(name, &location)?;
+
+        self.record(Operation::AddVault { path });
+        Ok(())
+    }
+
+    fn register_vault(&mut self, name: &str, location: &Path) -> Result<(), Error> {
+        check_vault_name(name)?;
+
+        if self.data.vault_exists(name) {
+            return Err(Error::VaultAlreadyExists(name.to_owned()));
+        }
+
+        let data_path = join_paths(vec![location, Path::new(name), Path::new(".jot/data")]);
+
+        Vault::load_path(data_path)?.set_path_data(name.to_owned(), location.to_path_buf())?;
 
-        self.data.add_vault(name.to_owned(), location);
+        self.data.add_vault(name.to_owned(), location.to_path_buf())
+    }
+
+    // @desc: Registers the vaults found under root, e.g. after cloning them onto a new machine.
+    //
+    // @notes:
+    //      -> Vaults are registered under the name of their folder. A vault whose data stores
+    //         another name (e.g. it was renamed outside of jot) is reported as a name clash rather
+    //         than registered, so that it can be sorted out with "jt vault add".
+    //      -> A registered vault whose folder no longer exists is taken to have been relocated to
+    //         the folder found with its name, which is journaled as a relink (so undoing it only
+    //         points the registration back, without moving any folder).
+    pub fn scan_vaults(&mut self, root: &Path, depth: usize) -> Result<ScanReport, Error> {
+        if !root.is_absolute() {
+            return Err(Error::PathNotAbsolute);
+        }
+
+        let root = resolve_path(root)?;
+        let vaults = find_vaults(&root, depth)
+            .into_iter()
+            .map(|path| self.scan_vault(path))
+            .collect();
+
+        Ok(ScanReport::new(vaults))
+    }
+
+    fn scan_vault(&mut self, path: PathBuf) -> ScannedVault {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let location = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+        let result = Vault::load_path(path.join(".jot/data")).and_then(|vault| {
+            let stored_name = vault
+                .get_stored_name()
+                .filter(|stored_name| **stored_name != name);
+
+            match self.generate_vault_path(&name) {
+                Ok(registered_path) if registered_path == path => {
+                    Ok((ScanStatus::Registered, None))
+                }
+                _ if stored_name.is_some() => Ok((
+                    ScanStatus::NameClash,
+                    Some(format!("named {} in its data", stored_name.unwrap())),
+                )),
+                Ok(registered_path) if registered_path.join(".jot/data").is_file() => Ok((
+                    ScanStatus::NameClash,
+                    Some(format!(
+                        "already registered at {}",
+                        registered_path.display()
+                    )),
+                )),
+                Ok(registered_path) => {
+                    self.relocate_vault(&name, &location)?;
+                    self.record(Operation::Relink {
+                        path: registered_path.to_owned(),
+                        new_path: path.to_owned(),
+                    });
+                    Ok((
+                        ScanStatus::Relocated,
+                        Some(format!("was registered at {}", registered_path.display())),
+                    ))
+                }
+                Err(_) => {
+                    self.register_vault(&name, &location)?;
+                    self.record(Operation::AddVault {
+                        path: path.to_owned(),
+                    });
+
+                    match vault.get_stored_path() {
+                        Some(stored_path) if stored_path != path => Ok((
+                            ScanStatus::Relocated,
+                            Some(format!("was stored at {}", stored_path.display())),
+                        )),
+                        _ => Ok((ScanStatus::Added, None)),
+                    }
+                }
+            }
+        });
+
+        match result {
+            Ok((status, detail)) => ScannedVault::new(name, path, status, detail),
+            Err(error) => ScannedVault::new(
+                name,
+                path,
+                ScanStatus::Failed,
+                Some(strip_ansi(&error.to_string())),
+            ),
+        }
+    }
+
+    // @desc: Points the registration of a vault and its own data to its new location.
+    fn relocate_vault(&mut self, name: &str, location: &Path) -> Result<(), Error> {
+        let data_path = join_paths(vec![location, Path::new(name), Path::new(".jot/data")]);
+
+        Vault::load_path(data_path)?.set_path_data(name.to_owned(), location.to_path_buf())?;
+
+        self.data.set_vault_location(name, location.to_path_buf())
+    }
+
+    // @desc: Points the registration of a vault back to an earlier location, leaving its folder
+    //        and its own data as they are.
+    fn relink_vault(&mut self, name: &str, location: &Path) -> Result<(), Error> {
+        if !self.data.vault_exists(name) {
+            return Err(Error::VaultNotFound(name.to_owned()));
+        }
+
+        self.data.set_vault_location(name, location.to_path_buf())
+    }
 
+    // @desc: Unregisters a vault, without deleting anything. Its folder keeps its vault data, so
+    //        it can be added again later.
+    pub fn forget_vault(&mut self, name: &str) -> Result<(), Error> {
+        let path = self.generate_vault_path(name)?;
+        self.unregister_vault(name)?;
+
+        self.record(Operation::ForgetVault { path });
         Ok(())
     }
 
-    pub fn remove_vault(&mut self, name: &str) -> Result<(), Error> {
+    fn unregister_vault(&mut self, name: &str) -> Result<(), Error> {
+        if !self.data.vault_exists(name) {
+            return Err(Error::VaultNotFound(name.to_owned()));
+        }
+
+        self.data.remove_vault(name)?;
+
+        if let Some(current_vault_name) = self.data.get_current_vault() {
+            if name == current_vault_name {
+                self.data.set_current_vault(None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // @desc: Checks the registry of vaults against the vaults' own data, and fixes the problems
+    //        found that confirm agrees to.
+    pub fn doctor(&mut self, confirm: &dyn Fn(&Problem) -> bool) -> DoctorReport {
+        let findings = self
+            .diagnose()
+            .into_iter()
+            .map(|problem| {
+                let mut finding = Finding::new(problem.to_owned());
+                if problem.fix().is_some() && confirm(&problem) {
+                    finding.set_result(self.fix(&problem));
+                }
+                finding
+            })
+            .collect();
+
+        DoctorReport::new(findings)
+    }
+
+    // @notes:
+    //      -> At most one problem is reported per vault, since later checks depend on earlier
+    //         ones (e.g. the folder of a vault whose data is mismatched is left unchecked, since
+    //         resetting it would write to wherever the data says).
+    fn diagnose(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+
+        if let Some(current_vault_name) = self.data.get_current_vault() {
+            if !self.data.vault_exists(current_vault_name) {
+                problems.push(Problem::UnknownCurrentVault {
+                    vault: current_vault_name.to_owned(),
+                });
+            }
+        }
+
+        let mut vaults: Vec<(&String, &PathBuf)> = self.data.get_vaults().iter().collect();
+        vaults.sort();
+
+        for (name, location) in vaults {
+            let vault = name.to_owned();
+            let path = join_paths(vec![location, &PathBuf::from(name)]);
+            let data_path = path.join(".jot/data");
+
+            if !path.is_dir() {
+                problems.push(Problem::MissingVault { vault, path });
+                continue;
+            }
+
+            if !data_path.is_file() {
+                problems.push(Problem::MissingData { vault, path });
+                continue;
+            }
+
+            let parsed = match read_to_string(&data_path) {
+                Ok(file_string) => Vault::parse(&data_path, &file_string),
+                Err(error) => Err(Error::Undefined(error)),
+            };
+            let stored = match parsed {
+                Ok((stored, _)) => stored,
+                Err(error) => {
+                    let message = strip_ansi(&error.to_string());
+                    problems.push(match error {
+                        Error::FileUnparsable(..) => Problem::UnparsableData {
+                            vault,
+                            path,
+                            error: message,
+                        },
+                        _ => Problem::UnreadableData {
+                            vault,
+                            path,
+                            error: message,
+                        },
+                    });
+                    continue;
+                }
+            };
+
+            let stored_path = stored.get_stored_path();
+            if stored_path.as_ref() != Some(&path) {
+                problems.push(Problem::MismatchedData {
+                    vault,
+                    path,
+                    stored_path,
+                });
+            } else if !path.join(stored.get_folder()).is_dir() {
+                problems.push(Problem::MissingFolder {
+                    vault,
+                    folder: stored.get_folder().to_owned(),
+                });
+            }
+        }
+
+        problems
+    }
+
+    fn fix(&mut self, problem: &Problem) -> Result<(), Error> {
+        let data_path = |path: &Path| path.join(".jot/data");
+
+        match problem {
+            Problem::UnknownCurrentVault { .. } => self.data.set_current_vault(None),
+            Problem::MissingVault { vault, .. } => self.forget_vault(vault),
+            Problem::MissingData { vault, path } | Problem::MismatchedData { vault, path, .. } => {
+                Vault::load_path(data_path(path))?
+                    .set_path_data(vault.to_owned(), path.parent().unwrap().to_path_buf())
+            }
+            Problem::UnparsableData { vault, path, .. } => {
+                Vault::load_or_recover(data_path(path), &|_, _| true)?
+                    .set_path_data(vault.to_owned(), path.parent().unwrap().to_path_buf())
+            }
+            Problem::MissingFolder { vault, .. } => {
+                Vault::load_path(data_path(&self.generate_vault_path(vault)?))?
+                    .set_folder(PathBuf::new())
+            }
+            Problem::UnreadableData { .. } => Ok(()),
+        }
+    }
+
+    // @desc: Moves the vault to the global trash and returns its id there.
+    pub fn remove_vault(&mut self, name: &str) -> Result<String, Error> {
+        let path = self.generate_vault_path(name)?;
+        let id = self.trash_vault(name)?;
+
+        self.record(Operation::Remove {
+            item: Item::Vl,
+            path,
+            trash_id: id.to_owned(),
+        });
+        Ok(id)
+    }
+
+    fn trash_vault(&mut self, name: &str) -> Result<String, Error> {
         if let Some(vault_location) = self.data.get_vault_location(name) {
-            remove_item(Item::Vl, name, vault_location)?;
-            self.data.remove_vault(name);
+            let path = generate_item_path(&Item::Vl, name, vault_location, &NoteTypes::default())?;
+            if !path.exists() {
+                return Err(Error::ItemNotFound(Item::Vl, name.to_owned()));
+            }
+
+            let id = self
+                .load_trash()?
+                .add(Item::Vl, &path, vault_location.to_owned())?;
+            self.data.remove_vault(name)?;
 
             if let Some(current_vault_name) = self.data.get_current_vault() {
                 if name == current_vault_name {
-                    self.data.set_current_vault(None);
+                    self.data.set_current_vault(None)?;
                 }
             }
 
-            Ok(())
+            Ok(id)
         } else {
             Err(Error::VaultNotFound(name.to_owned()))
         }
     }
 
+    // @desc: Lists items in the current vault's trash followed by vaults in the global trash.
+    pub fn list_trash(&self) -> Result<Vec<TrashEntry>, Error> {
+        let mut entries = vec![];
+
+        if let Some(vault) = &self.current {
+            entries.extend(vault.load_trash()?.get_entries().to_owned());
+        }
+        entries.extend(self.load_trash()?.get_entries().to_owned());
+
+        Ok(entries)
+    }
+
+    // @desc: Restores an item from the current vault's trash, or a vault from the global trash.
+    //
+    // @notes:
+    //      -> Restored vaults are registered again under their old name and location.
+    pub fn restore_from_trash(&mut self, id: &str) -> Result<TrashEntry, Error> {
+        let mut trash = self.load_trash()?;
+
+        if let Some(entry) = trash
+            .get_entries()
+            .iter()
+            .find(|entry| entry.get_id() == id)
+        {
+            if self.data.vault_exists(&entry.get_name()) {
+                return Err(Error::VaultAlreadyExists(entry.get_name()));
+            }
+
+            let entry = trash.restore(id, Path::new(""))?;
+            self.data
+                .add_vault(entry.get_name(), entry.get_origin().to_owned())?;
+
+            return Ok(entry);
+        }
+
+        match &self.current {
+            Some(vault) => vault.restore_from_trash(id),
+            None => Err(Error::TrashItemNotFound(id.to_owned())),
+        }
+    }
+
+    // @desc: Empties the current vault's trash and the global trash, returning the number of
+    //        items deleted.
+    pub fn empty_trash(&self, older_than: Option<Duration>) -> Result<usize, Error> {
+        let mut count = 0;
+
+        if let Some(vault) = &self.current {
+            count += vault.load_trash()?.empty(older_than)?;
+        }
+        count += self.load_trash()?.empty(older_than)?;
+
+        Ok(count)
+    }
+
+    fn load_trash(&self) -> Result<Trash, Error> {
+        Trash::load_root(&self.data.get_trash_dir(), "v")
+    }
+
+    // @desc: Lists the latest operations from the current vault's journal and the global one,
+    //        oldest first.
+    pub fn log(&self, count: usize) -> Result<Vec<JournalEntry>, Error> {
+        let mut entries = self.load_journal()?.get_entries().to_owned();
+
+        if let Some(vault) = &self.current {
+            entries.extend(vault.load_journal()?.get_entries().to_owned());
+        }
+        entries.sort_by(|a, b| a.get_time().cmp(b.get_time()));
+
+        Ok(entries.split_off(entries.len().saturating_sub(count)))
+    }
+
+    // @desc: Reverses the latest operation that hasn't been undone yet, from either the current
+    //        vault's journal or the global one, and returns its entry.
+    //
+    // @notes:
+    //      -> Undoing doesn't record the reversing operation itself, only a marker for the undone
+    //         entry, so that repeated undos keep going further back.
+    pub fn undo(&mut self, config: &mut Config) -> Result<JournalEntry, Error> {
+        let mut global = self.load_journal()?;
+        let mut local = match &self.current {
+            Some(vault) => Some(vault.load_journal()?),
+            None => None,
+        };
+
+        let global_entry = global.last_undoable().cloned();
+        let local_entry = local
+            .as_ref()
+            .and_then(|journal| journal.last_undoable().cloned());
+
+        let (journal, entry) = match (local_entry, global_entry) {
+            (Some(local_entry), Some(global_entry))
+                if global_entry.get_time() > local_entry.get_time() =>
+            {
+                (&mut global, global_entry)
+            }
+            (Some(local_entry), _) => (local.as_mut().unwrap(), local_entry),
+            (None, Some(global_entry)) => (&mut global, global_entry),
+            (None, None) => return Err(Error::NothingToUndo),
+        };
+
+        if entry.get_id().starts_with('g') {
+            self.undo_global(entry.get_operation(), config)?;
+        } else if let Operation::VMove { item, path, vault } = entry.get_operation() {
+            self.undo_vmove(item, path, vault)?;
+        } else {
+            self.mut_current()?.undo(entry.get_operation())?;
+        }
+
+        journal.record(Operation::Undo {
+            id: entry.get_id().to_owned(),
+        })?;
+        Ok(entry)
+    }
+
+    // @desc: Records an operation on vaults or app config in the global journal.
+    pub fn record(&self, operation: Operation) {
+        if let Ok(mut journal) = self.load_journal() {
+            journal.record(operation).ok();
+        }
+    }
+
+    fn undo_global(&mut self, operation: &Operation, config: &mut Config) -> Result<(), Error> {
+        let file_name = |path: &Path| path.file_name().unwrap().to_string_lossy().to_string();
+
+        match operation {
+            Operation::Create { path, .. } => {
+                self.trash_vault(&file_name(path))?;
+            }
+            Operation::Remove { trash_id, .. } => {
+                self.restore_from_trash(trash_id)?;
+            }
+            Operation::Rename { path, new_path, .. } => {
+                self.rename_vault_entry(&file_name(new_path), &file_name(path))?
+            }
+            Operation::Move { path, .. } => {
+                self.move_vault_entry(&file_name(path), path.parent().unwrap())?
+            }
+            Operation::AddVault { path } => self.unregister_vault(&file_name(path))?,
+            Operation::Relink { path, .. } => {
+                self.relink_vault(&file_name(path), path.parent().unwrap())?
+            }
+            Operation::ForgetVault { path } => {
+                self.register_vault(&file_name(path), path.parent().unwrap())?
+            }
+            Operation::ConfigSet { key, value, .. } => config.set_config(key, value)?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // @desc: Moves an item from the root of the vault it was moved to, back to where it was in
+    //        the current vault.
+    fn undo_vmove(&self, item: &Item, path: &Path, vault_name: &str) -> Result<(), Error> {
+        let vault_path = self.generate_vault_path(vault_name)?;
+        let current = self.ref_current()?;
+        let (name, location, _) = current.get_path_data();
+
+        let original_location = join_paths(vec![location, &PathBuf::from(name)])
+            .join(path.parent().unwrap_or(Path::new("")));
+        let name = path.file_name().unwrap().to_string_lossy();
+
+        move_item(
+            item.to_owned(),
+            &name,
+            &vault_path,
+            &original_location,
+            &current.get_note_types(),
+        )?;
+        Ok(())
+    }
+
+    fn load_journal(&self) -> Result<Journal, Error> {
+        Journal::load_path(&self.data.get_journal_path(), "g")
+    }
+
+    fn generate_vault_path(&self, name: &str) -> Result<PathBuf, Error> {
+        match self.data.get_vault_location(name) {
+            Some(location) => Ok(join_paths(vec![location, &PathBuf::from(name)])),
+            None => Err(Error::VaultNotFound(name.to_owned())),
+        }
+    }
+
     pub fn rename_vault(&mut self, name: &str, new_name: &str) -> Result<(), Error> {
+        let path = self.generate_vault_path(name)?;
+        self.rename_vault_entry(name, new_name)?;
+
+        self.record(Operation::Rename {
+            item: Item::Vl,
+            new_path: path.with_file_name(new_name),
+            path,
+            update_links: false,
+        });
+        Ok(())
+    }
+
+    fn rename_vault_entry(&mut self, name: &str, new_name: &str) -> Result<(), Error> {
+        check_vault_name(new_name)?;
+
         if self.data.vault_exists(new_name) {
             return Err(Error::VaultAlreadyExists(new_name.to_owned()));
         }
 
         if let Some(vault_location) = self.data.get_vault_location(name) {
-            let path = rename_item(Item::Vl, name, new_name, vault_location)?;
+            let path = rename_item(
+                Item::Vl,
+                name,
+                new_name,
+                vault_location,
+                &NoteTypes::default(),
+            )?;
             let data_path = join_paths(vec![path.to_str().unwrap(), ".jot/data"]);
 
-            Vault::load_path(data_path).set_name(new_name.to_owned());
-            self.data.rename_vault(name, new_name.to_owned());
+            Vault::load_path(data_path)?.set_name(new_name.to_owned())?;
+            self.data.rename_vault(name, new_name.to_owned())?;
 
             if let Some(current_vault) = self.data.get_current_vault() {
                 if name == current_vault {
-                    self.data.set_current_vault(Some(new_name.to_owned()));
+                    self.data.set_current_vault(Some(new_name.to_owned()))?;
                 }
             }
 
@@ -145,17 +755,36 @@ impl Vaults {
     }
 
     pub fn move_vault(&mut self, name: &str, new_location: &Path) -> Result<(), Error> {
+        let path = self.generate_vault_path(name)?;
+        self.move_vault_entry(name, new_location)?;
+
+        self.record(Operation::Move {
+            item: Item::Vl,
+            path,
+            new_path: self.generate_vault_path(name)?,
+            update_links: false,
+        });
+        Ok(())
+    }
+
+    fn move_vault_entry(&mut self, name: &str, new_location: &Path) -> Result<(), Error> {
         if !new_location.is_absolute() {
             return Err(Error::PathNotAbsolute);
         }
 
         if let Some(original_location) = self.data.get_vault_location(name) {
-            let new_path = move_item(Item::Vl, name, original_location, new_location)?;
+            let new_path = move_item(
+                Item::Vl,
+                name,
+                original_location,
+                new_location,
+                &NoteTypes::default(),
+            )?;
             let data_path = join_paths(vec![new_path.to_str().unwrap(), ".jot/data"]);
 
             let new_location = resolve_path(new_location)?;
-            Vault::load_path(data_path).set_location(new_location.to_owned());
-            self.data.set_vault_location(name, new_location);
+            Vault::load_path(data_path)?.set_location(new_location.to_owned())?;
+            self.data.set_vault_location(name, new_location)?;
 
             Ok(())
         } else {
@@ -190,8 +819,16 @@ impl Vaults {
             }
         }
 
-        self.data.set_current_vault(Some(name.to_owned()));
+        self.data.set_current_vault(Some(name.to_owned()))?;
 
         Ok(())
     }
 }
+
+fn check_vault_name(name: &str) -> Result<(), Error> {
+    if RESERVED_VAULT_NAMES.contains(&name) {
+        return Err(Error::ReservedVaultName(name.to_owned()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanStatus {
+    Added,
+    Relocated,
+    Registered,
+    NameClash,
+    Failed,
+}
+
+impl ScanStatus {
+    fn to_str(self) -> &'static str {
+        match self {
+            ScanStatus::Added => "added",
+            ScanStatus::Relocated => "relocated",
+            ScanStatus::Registered => "registered",
+            ScanStatus::NameClash => "name clash",
+            ScanStatus::Failed => "failed",
+        }
+    }
+}
+
+// @desc: A vault found by "jt vault scan", and what was done with it.
+//
+// @notes:
+//      -> Relocated vaults were registered (or their registration was updated), but their data
+//         or the registry pointed somewhere else, which "detail" tells.
+//      -> Vaults already registered at the same path are left as they are. Vaults whose name is
+//         taken by another registered vault, or whose data stores another name, are skipped.
+//      -> Failed vaults couldn't be read or registered, "detail" holds the error.
+#[derive(Debug, Serialize)]
+pub struct ScannedVault {
+    name: String,
+    path: PathBuf,
+    status: ScanStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl ScannedVault {
+    pub fn new(name: String, path: PathBuf, status: ScanStatus, detail: Option<String>) -> Self {
+        ScannedVault {
+            name,
+            path,
+            status,
+            detail,
+        }
+    }
+}
+
+impl Display for ScannedVault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} \t \x1b[0;34m{}\x1b[0m \t {}",
+            self.status.to_str(),
+            self.name,
+            self.path.display()
+        )?;
+
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanReport {
+    vaults: Vec<ScannedVault>,
+}
+
+impl ScanReport {
+    pub fn new(vaults: Vec<ScannedVault>) -> Self {
+        ScanReport { vaults }
+    }
+}
+
+impl Display for ScanReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.vaults.is_empty() {
+            return write!(f, "no vaults found");
+        }
+
+        let lines: Vec<String> = self.vaults.iter().map(|vault| vault.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// @desc: Finds folders containing vault data ("<folder>/.jot/data") under root, up to depth
+//        levels below it, sorted by path.
+//
+// @notes:
+//      -> Vaults aren't searched for other vaults, and hidden folders and symlinks aren't
+//         followed (which keeps scans of e.g. home folders quick and free of loops).
+//      -> Folders that can't be read are skipped.
+pub fn find_vaults(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut vaults = vec![];
+    find_vaults_collect(root, depth, &mut vaults);
+
+    vaults.sort();
+    vaults
+}
+
+fn find_vaults_collect(path: &Path, depth: usize, vaults: &mut Vec<PathBuf>) {
+    if path.join(".jot/data").is_file() {
+        vaults.push(path.to_path_buf());
+        return;
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    let entries = match path.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let is_dir = entry
+            .file_type()
+            .map(|file_type| file_type.is_dir())
+            .unwrap_or(false);
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if is_dir && !is_hidden {
+            find_vaults_collect(&entry.path(), depth - 1, vaults);
+        }
+    }
+}
@@ -1,35 +1,75 @@
 use crate::{
-    enums::VaultItem,
+    enums::{ConfigType, Item, VaultItem},
+    notes::{
+        fuzzy::{find_notes, NoteMatch, Pick, MAX_CANDIDATES},
+        links::{Backlink, LinkReport, LinkResolver, LinkUpdate},
+        note::Note,
+        search::{build_pattern, search_notes, SearchHit},
+        tags::{note_tags, tag_matches},
+        template::{find_template, render_template},
+        types::{is_markdown, NoteTypes, NoteTypesConfig},
+    },
     output::error::Error,
+    state::{
+        editor::Editor,
+        journal::{Journal, Operation},
+        trash::{Trash, TrashEntry},
+        vaults::{
+            daily::{days_in_month, DailyCalendar, DailyConfig},
+            history::{parse_history_ref, push_history, HistoryEntry, HistoryKind},
+            index::Index,
+            listing::FolderTree,
+        },
+    },
     traits::FileIO,
     utils::{
-        create_item, filtered_list, join_paths, move_item, open_folder, open_note, rec_list,
-        remove_item, rename_item, resolve_path,
+        create_item, generate_item_path, join_paths, move_item, open_folder, open_note,
+        rename_item, resolve_path, valid_name, walk_notes, NoteFilter,
     },
 };
+use chrono::{Datelike, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir, create_dir_all, rename},
+    path::{Component, Path, PathBuf},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Vault {
+    version: u32,
     name: Option<String>,
     location: Option<PathBuf>,
     folder: PathBuf,
-    history: Vec<(String, PathBuf)>,
+    history: Vec<HistoryEntry>,
+    #[serde(default)]
+    daily: DailyConfig,
+    #[serde(default)]
+    note_types: NoteTypesConfig,
+    // Global note types, which the vault's own fall back to. Set after loading, since they
+    // come from config.
+    #[serde(skip)]
+    global_note_types: NoteTypesConfig,
 }
 
 impl Default for Vault {
     fn default() -> Self {
         Vault {
+            version: Self::VERSION,
             name: None,
             location: None,
             folder: PathBuf::new(),
             history: vec![],
+            daily: DailyConfig::default(),
+            note_types: NoteTypesConfig::default(),
+            global_note_types: NoteTypesConfig::default(),
         }
     }
 }
 
 impl FileIO for Vault {
+    const VERSION: u32 = 1;
+
     fn path(&self) -> PathBuf {
         join_paths(vec![
             self.get_location().to_str().unwrap(),
@@ -44,10 +84,34 @@ impl Vault {
         self.name.as_ref().unwrap()
     }
 
-    pub fn set_name(&mut self, name: String) {
-        self.name = Some(name);
+    // The path of the file depends on the name and location, so they're set before the file is
+    // modified, and set again once it's reloaded.
+    pub fn set_name(&mut self, name: String) -> Result<(), Error> {
+        self.name = Some(name.to_owned());
         if self.location.is_some() {
-            self.store()
+            self.modify_data(|vault| {
+                vault.name = Some(name);
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn has_path_data(&self) -> bool {
+        self.name.is_some() && self.location.is_some()
+    }
+
+    pub fn get_stored_name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    // @desc: Generates the path of the vault from its stored name and location, which may be
+    //        outdated if the vault was moved or renamed outside of jot.
+    pub fn get_stored_path(&self) -> Option<PathBuf> {
+        match (&self.location, &self.name) {
+            (Some(location), Some(name)) => Some(location.join(name)),
+            _ => None,
         }
     }
 
@@ -55,55 +119,325 @@ impl Vault {
         self.location.as_ref().unwrap()
     }
 
-    pub fn set_location(&mut self, location: PathBuf) {
-        self.location = Some(location);
+    pub fn set_location(&mut self, location: PathBuf) -> Result<(), Error> {
+        self.location = Some(location.to_owned());
         if self.name.is_some() {
-            self.store()
+            self.modify_data(|vault| {
+                vault.location = Some(location);
+                Ok(())
+            })?;
         }
+
+        Ok(())
     }
 
     pub fn get_folder(&self) -> &PathBuf {
         &self.folder
     }
 
-    pub fn set_folder(&mut self, folder: PathBuf) {
-        self.folder = folder;
-        self.store()
+    pub fn set_folder(&mut self, folder: PathBuf) -> Result<(), Error> {
+        self.modify_data(|vault| {
+            vault.folder = folder;
+            Ok(())
+        })
+    }
+
+    pub fn get_history(&self) -> &Vec<HistoryEntry> {
+        &self.history
+    }
+
+    pub fn add_to_history(&mut self, kind: HistoryKind, path: PathBuf) -> Result<(), Error> {
+        self.modify_data(|vault| {
+            push_history(&mut vault.history, kind, path);
+            Ok(())
+        })
+    }
+
+    // @desc: Sets both name and location, for when the ones stored may point to another folder
+    //        (which the file would be stored to otherwise).
+    pub fn set_path_data(&mut self, name: String, location: PathBuf) -> Result<(), Error> {
+        self.name = Some(name.to_owned());
+        self.location = Some(location.to_owned());
+        self.modify_data(|vault| {
+            vault.name = Some(name);
+            vault.location = Some(location);
+            Ok(())
+        })
     }
 
     pub fn get_path_data(&self) -> (&String, &PathBuf, &PathBuf) {
         (self.get_name(), self.get_location(), self.get_folder())
     }
+
+    pub fn set_global_note_types(&mut self, note_types: &NoteTypesConfig) {
+        self.global_note_types = note_types.to_owned();
+    }
+
+    // @desc: Resolves the note types of the vault, falling back to the global ones.
+    pub fn get_note_types(&self) -> NoteTypes {
+        NoteTypes::resolve(&self.note_types, &self.global_note_types)
+    }
+
+    // @desc: Wraps FileIO::modify(), which reloads the file and so would drop the global note
+    //        types (as they aren't stored).
+    fn modify_data<F>(&mut self, change: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        let global_note_types = self.global_note_types.to_owned();
+        let result = self.modify(change);
+        self.global_note_types = global_note_types;
+
+        result
+    }
+
+    pub fn set_config(&mut self, config_type: &ConfigType, value: &str) -> Result<(), Error> {
+        let old_value = self.get_config(config_type);
+        self.apply_config(config_type, value)?;
+
+        self.record(Operation::ConfigSet {
+            key: config_type.to_owned(),
+            value: old_value,
+            new_value: value.to_owned(),
+        });
+        Ok(())
+    }
+
+    fn apply_config(&mut self, config_type: &ConfigType, value: &str) -> Result<(), Error> {
+        self.modify_data(|vault| {
+            match config_type {
+                ConfigType::DailyFolder => vault.daily.set_folder(value)?,
+                ConfigType::DailyFormat => vault.daily.set_format(value.to_owned())?,
+                ConfigType::DailyTemplate => vault.daily.set_template(value.to_owned()),
+                ConfigType::VaultExtensions => vault.note_types.set_extensions(value)?,
+                ConfigType::VaultDefaultExtension => {
+                    vault.note_types.set_default_extension(value)?
+                }
+                _ => (),
+            }
+            Ok(())
+        })
+    }
+
+    pub fn get_config(&self, config_type: &ConfigType) -> String {
+        match config_type {
+            ConfigType::DailyFolder => self.daily.get_folder().display().to_string(),
+            ConfigType::DailyFormat => self.daily.get_format().to_owned(),
+            ConfigType::DailyTemplate => self
+                .daily
+                .get_template()
+                .map(|template| template.to_owned())
+                .unwrap_or_default(),
+            ConfigType::VaultExtensions => self.note_types.get_extensions(),
+            ConfigType::VaultDefaultExtension => self.note_types.get_default_extension(),
+            _ => "".to_string(),
+        }
+    }
 }
 
 impl Vault {
-    pub fn create_vault_item(&self, item_type: VaultItem, name: &str) -> Result<(), Error> {
-        let location = self.generate_location();
+    // @notes:
+    //      -> Missing folders in the path of the item are only created when "parents" is set.
+    pub fn create_vault_item(
+        &self,
+        item_type: VaultItem,
+        name: &str,
+        parents: bool,
+    ) -> Result<(), Error> {
+        let (location, name) = self.split_item_path(name)?;
+        let name = name.as_str();
+        if parents {
+            self.create_parents(&location)?;
+        }
 
-        create_item(item_type.to_item(), name, &location)?;
+        let path = if let VaultItem::Note | VaultItem::Nt = item_type {
+            self.create_note(&location, name, Note::default())?
+        } else {
+            create_item(item_type.to_item(), name, &location, &self.get_note_types())?
+        };
 
+        self.record(Operation::Create {
+            item: item_type.to_item(),
+            path: self.relative_path(&path),
+        });
         Ok(())
     }
 
-    pub fn remove_vault_item(&self, item_type: VaultItem, name: &str) -> Result<(), Error> {
-        let location = self.generate_location();
+    pub fn create_note_from_template(
+        &self,
+        name: &str,
+        template: &str,
+        vars: &[(String, String)],
+        global_templates_dir: &Path,
+        parents: bool,
+    ) -> Result<(), Error> {
+        let (location, name) = self.split_item_path(name)?;
+
+        let note = self.render_note(
+            &self.get_note_types().strip_extension(&name),
+            &self.relative_path(&location),
+            template,
+            vars,
+            global_templates_dir,
+        )?;
 
-        remove_item(item_type.to_item(), name, &location)?;
+        if parents {
+            self.create_parents(&location)?;
+        }
+        let path = self.create_note(&location, &name, note)?;
 
+        self.record(Operation::Create {
+            item: Item::Nt,
+            path: self.relative_path(&path),
+        });
         Ok(())
     }
 
+    // @desc: Opens the daily note for a date, creating it first (from the daily template, or the
+    //        default one) if it doesn't exist. Returns the name of the note if it was created.
+    //
+    // @notes:
+    //      -> The "date" template variable is set to the date of the daily note.
+    pub fn open_daily(
+        &mut self,
+        date: &NaiveDate,
+        editor: &Editor,
+        global_templates_dir: &Path,
+        default_template: Option<&String>,
+    ) -> Result<Option<String>, Error> {
+        let name = self.daily.note_name(date);
+        let folder = self.daily.get_folder();
+        // The folder is checked when set, but the data file could have been edited by hand.
+        if folder.is_absolute() || folder.components().any(|c| c == Component::ParentDir) {
+            return Err(Error::OutOfBounds);
+        }
+        let location = join_paths(vec![&self.generate_vault_path(), folder]);
+
+        let mut path = generate_item_path(&Item::Nt, &name, &location, &self.get_note_types())?;
+        let mut created = None;
+
+        if !path.exists() {
+            if let Err(error) = create_dir_all(&location) {
+                return Err(Error::Undefined(error));
+            }
+
+            let note = match self.daily.get_template().or(default_template) {
+                Some(template) => self.render_note(
+                    &name,
+                    folder,
+                    template,
+                    &[("date".to_string(), date.format("%Y-%m-%d").to_string())],
+                    global_templates_dir,
+                )?,
+                None => Note::default(),
+            };
+
+            path = self.create_note(&location, &name, note)?;
+            created = Some(name);
+        }
+
+        editor.open(&path, None)?;
+
+        let path = match path.strip_prefix(self.generate_vault_path()) {
+            Ok(path) => path.to_path_buf(),
+            Err(_) => return Err(Error::OutOfBounds),
+        };
+        self.add_to_history(HistoryKind::Note, path)?;
+        Ok(created)
+    }
+
+    // @desc: Finds the days of a month that have daily notes.
+    pub fn list_daily(&self, month: NaiveDate) -> DailyCalendar {
+        let location = join_paths(vec![&self.generate_vault_path(), self.daily.get_folder()]);
+
+        let days = (1..=days_in_month(&month))
+            .filter(|day| {
+                let date = month.with_day(*day).unwrap();
+                generate_item_path(
+                    &Item::Nt,
+                    &self.daily.note_name(&date),
+                    &location,
+                    &self.get_note_types(),
+                )
+                .is_ok_and(|path| path.exists())
+            })
+            .collect();
+
+        DailyCalendar::new(month, days)
+    }
+
+    // @desc: Moves the item to the vault's trash and returns its id there.
+    pub fn remove_vault_item(&self, item_type: VaultItem, name: &str) -> Result<String, Error> {
+        let (location, item_name) = self.split_item_path(name)?;
+
+        let path = generate_item_path(
+            &item_type.to_item(),
+            &item_name,
+            &location,
+            &self.get_note_types(),
+        )?;
+        if !path.exists() {
+            return Err(Error::ItemNotFound(item_type.to_item(), name.to_owned()));
+        }
+
+        let id =
+            self.load_trash()?
+                .add(item_type.to_item(), &path, self.relative_path(&location))?;
+        self.update_index(|index| index.remove_path(&path));
+
+        self.record(Operation::Remove {
+            item: item_type.to_item(),
+            path: self.relative_path(&path),
+            trash_id: id.to_owned(),
+        });
+        Ok(id)
+    }
+
+    pub fn load_trash(&self) -> Result<Trash, Error> {
+        Trash::load_root(&self.generate_vault_path().join(".jot/trash"), "")
+    }
+
+    pub fn restore_from_trash(&self, id: &str) -> Result<TrashEntry, Error> {
+        let entry = self
+            .load_trash()?
+            .restore(id, &self.generate_vault_path())?;
+        self.update_index(|index| index.refresh());
+
+        Ok(entry)
+    }
+
     pub fn rename_vault_item(
         &self,
         item_type: VaultItem,
         name: &str,
         new_name: &str,
-    ) -> Result<(), Error> {
-        let location = self.generate_location();
+        update_links: bool,
+    ) -> Result<LinkUpdate, Error> {
+        let (location, name) = self.split_item_path(name)?;
 
-        rename_item(item_type.to_item(), name, new_name, &location)?;
+        let path = generate_item_path(
+            &item_type.to_item(),
+            &name,
+            &location,
+            &self.get_note_types(),
+        )?;
+        let resolver = self.generate_link_resolver(update_links)?;
+        let new_path = rename_item(
+            item_type.to_item(),
+            &name,
+            new_name,
+            &location,
+            &self.get_note_types(),
+        )?;
+        self.update_index(|index| index.rename_path(&path, &new_path));
 
-        Ok(())
+        self.record(Operation::Rename {
+            item: item_type.to_item(),
+            path: self.relative_path(&path),
+            new_path: self.relative_path(&new_path),
+            update_links,
+        });
+        Ok(self.rewrite_links(resolver, &path, &new_path))
     }
 
     pub fn move_vault_item(
@@ -111,19 +445,43 @@ impl Vault {
         item_type: VaultItem,
         name: &str,
         new_location: &PathBuf,
-    ) -> Result<(), Error> {
+        update_links: bool,
+    ) -> Result<LinkUpdate, Error> {
         let vault_path = join_paths(vec![self.get_location().to_str().unwrap(), self.get_name()]);
-        let original_location = join_paths(vec![&vault_path, self.get_folder()]);
+        let current_location = join_paths(vec![&vault_path, self.get_folder()]);
 
-        let new_location = resolve_path(&join_paths(vec![&original_location, new_location]))?;
+        let new_location = resolve_path(&join_paths(vec![&current_location, new_location]))?;
 
         if !new_location.starts_with(vault_path) {
             return Err(Error::OutOfBounds);
         }
 
-        move_item(item_type.to_item(), name, &original_location, &new_location)?;
+        let (original_location, name) = self.split_item_path(name)?;
+        let name = name.as_str();
 
-        Ok(())
+        let path = generate_item_path(
+            &item_type.to_item(),
+            name,
+            &original_location,
+            &self.get_note_types(),
+        )?;
+        let resolver = self.generate_link_resolver(update_links)?;
+        let new_path = move_item(
+            item_type.to_item(),
+            name,
+            &original_location,
+            &new_location,
+            &self.get_note_types(),
+        )?;
+        self.update_index(|index| index.rename_path(&path, &new_path));
+
+        self.record(Operation::Move {
+            item: item_type.to_item(),
+            path: self.relative_path(&path),
+            new_path: self.relative_path(&new_path),
+            update_links,
+        });
+        Ok(self.rewrite_links(resolver, &path, &new_path))
     }
 
     pub fn vmove_vault_item(
@@ -133,26 +491,149 @@ impl Vault {
         vault_name: &str,
         vault_location: &Path,
     ) -> Result<(), Error> {
-        let original_location = self.generate_location();
-
         if vault_name == self.get_name() {
-            print!(
-                "{} {} already exists in vault {}",
-                item_type.full(),
-                name,
-                vault_name
-            )
+            return Err(Error::AlreadyInVault(vault_name.to_owned()));
         }
 
+        let (original_location, name) = self.split_item_path(name)?;
+        let name = name.as_str();
+
         let new_location = join_paths(vec![vault_location.to_str().unwrap(), vault_name]);
-        move_item(item_type.to_item(), name, &original_location, &new_location)?;
+        let path = generate_item_path(
+            &item_type.to_item(),
+            name,
+            &original_location,
+            &self.get_note_types(),
+        )?;
+        move_item(
+            item_type.to_item(),
+            name,
+            &original_location,
+            &new_location,
+            &self.get_note_types(),
+        )?;
+        self.update_index(|index| index.remove_path(&path));
 
+        self.record(Operation::VMove {
+            item: item_type.to_item(),
+            path: self.relative_path(&path),
+            vault: vault_name.to_owned(),
+        });
         Ok(())
     }
 
-    pub fn open_note(&self, name: &str, editor_data: (&String, bool)) -> Result<(), Error> {
-        let location = self.generate_location();
-        open_note(editor_data, name, &location)?;
+    // @desc: Opens a note from the current folder, or a note from history when name is a
+    //        reference like "@1".
+    //
+    // @notes:
+    //      -> If there's no note with the name, the note is looked for fuzzily in the whole vault.
+    //      -> "line" is passed on to the editor, for command lines using "{line}".
+    pub fn open_note(
+        &mut self,
+        name: &str,
+        editor: &Editor,
+        line: Option<usize>,
+        pick: Pick,
+    ) -> Result<(), Error> {
+        let vault_path = self.generate_vault_path();
+
+        let path = if let Some(index) = parse_history_ref(name) {
+            let path = self.find_history_entry(name, index, HistoryKind::Note)?;
+            let path = join_paths(vec![&vault_path, &path]);
+
+            if !path.exists() {
+                return Err(Error::ItemNotFound(Item::Nt, name.to_owned()));
+            }
+
+            editor.open(&path, line)?;
+            path
+        } else {
+            let (location, item_name) = self.split_item_path(name)?;
+
+            match open_note(editor, line, &item_name, &location, &self.get_note_types()) {
+                Err(Error::ItemNotFound(..)) => {
+                    let path = self.find_note(name, pick)?;
+                    editor.open(&path, line)?;
+                    path
+                }
+                result => result?,
+            }
+        };
+
+        let path = path.strip_prefix(&vault_path).unwrap().to_path_buf();
+        self.add_to_history(HistoryKind::Note, path)?;
+        Ok(())
+    }
+
+    // @desc: Finds the note matching name fuzzily, letting pick choose one when several do.
+    //
+    // @notes:
+    //      -> A note matching exactly (by name, title or alias) is opened without asking, as long
+    //         as it's the only one.
+    fn find_note(&self, name: &str, pick: Pick) -> Result<PathBuf, Error> {
+        let vault_path = self.generate_vault_path();
+        let matches = find_notes(
+            name,
+            walk_notes(&vault_path, &self.get_note_types())?,
+            &vault_path,
+        );
+        let exact: Vec<&NoteMatch> = matches.iter().filter(|found| found.is_exact()).collect();
+
+        let found = match (matches.len(), exact.len()) {
+            (0, _) => return Err(Error::ItemNotFound(Item::Nt, name.to_owned())),
+            (1, _) => &matches[0],
+            (_, 1) => exact[0],
+            _ => {
+                let candidates = &matches[..matches.len().min(MAX_CANDIDATES)];
+
+                match pick(name, candidates) {
+                    Some(index) if index < candidates.len() => &candidates[index],
+                    _ => return Err(Error::AmbiguousNote(name.to_owned(), candidates.to_vec())),
+                }
+            }
+        };
+
+        Ok(vault_path.join(found.get_path()))
+    }
+
+    pub fn get_meta(&self, name: &str) -> Result<Vec<(String, String)>, Error> {
+        let path = self.generate_note_path(name)?;
+        Ok(Note::read(&path)?.frontmatter().fields())
+    }
+
+    pub fn get_meta_field(&self, name: &str, key: &str) -> Result<String, Error> {
+        let path = self.generate_note_path(name)?;
+
+        match Note::read(&path)?.frontmatter().get(key) {
+            Some(value) => Ok(value),
+            None => Err(Error::MetaKeyNotFound(key.to_owned())),
+        }
+    }
+
+    pub fn set_meta_field(&self, name: &str, key: &str, value: &str) -> Result<(), Error> {
+        let path = self.generate_note_path(name)?;
+
+        let mut note = Note::read(&path)?;
+        note.frontmatter_mut().set(key, value);
+        if key != "modified" {
+            note.stamp_modified();
+        }
+        note.write(&path)?;
+
+        self.update_index(|index| index.update_note(&path));
+        Ok(())
+    }
+
+    pub fn remove_meta_field(&self, name: &str, key: &str) -> Result<(), Error> {
+        let path = self.generate_note_path(name)?;
+
+        let mut note = Note::read(&path)?;
+        if !note.frontmatter_mut().remove(key) {
+            return Err(Error::MetaKeyNotFound(key.to_owned()));
+        }
+        note.write(&path)?;
+
+        self.update_index(|index| index.update_note(&path));
         Ok(())
     }
 
@@ -167,10 +648,30 @@ impl Vault {
         Ok(())
     }
 
+    // @desc: Changes the current folder. Besides paths, "-" switches back to the previous folder
+    //        and references like "@3" switch to a folder from history.
     pub fn change_folder(&mut self, path: &PathBuf) -> Result<(), Error> {
         let vault_path = join_paths(vec![self.get_location().to_str().unwrap(), self.get_name()]);
+
+        let reference = path.to_str().unwrap_or_default();
+        let path = if reference == "-" {
+            let previous = self.history.iter().find(|entry| {
+                entry.get_kind() == &HistoryKind::Folder && entry.get_path() != &self.folder
+            });
+
+            match previous {
+                Some(entry) => join_paths(vec![&vault_path, entry.get_path()]),
+                None => return Err(Error::NoPreviousFolder),
+            }
+        } else if let Some(index) = parse_history_ref(reference) {
+            let folder = self.find_history_entry(reference, index, HistoryKind::Folder)?;
+            join_paths(vec![&vault_path, &folder])
+        } else {
+            path.to_owned()
+        };
+
         let current_folder_abs = resolve_path(&join_paths(vec![&vault_path, self.get_folder()]))?;
-        let dest_folder_abs = resolve_path(&join_paths(vec![&current_folder_abs, path]))?;
+        let dest_folder_abs = resolve_path(&join_paths(vec![&current_folder_abs, &path]))?;
 
         if !dest_folder_abs.starts_with(&vault_path) {
             return Err(Error::OutOfBounds);
@@ -185,28 +686,426 @@ impl Vault {
             dest_folder = dest_folder.strip_prefix("/").unwrap();
         }
         let dest_folder = dest_folder.to_path_buf();
+        let folder = self.folder.to_owned();
 
-        self.set_folder(dest_folder);
+        // History has to be changed along with the folder, since the file is reloaded first.
+        self.modify_data(|vault| {
+            push_history(&mut vault.history, HistoryKind::Folder, folder.to_owned());
+            vault.folder = dest_folder.to_owned();
+            Ok(())
+        })?;
+        self.record(Operation::ChangeFolder {
+            folder,
+            new_folder: dest_folder,
+        });
 
         Ok(())
     }
 
-    pub fn list(&self, item_type: &Option<VaultItem>) {
-        let folder = self.get_folder();
+    pub fn list(&self, item_type: &Option<VaultItem>, tag: &Option<String>) -> FolderTree {
+        let location = self.generate_location();
 
-        if folder.as_os_str().is_empty() {
-            println!("{}", self.get_name());
+        let tag_filter = |path: &Path| {
+            let tag = tag.as_ref().unwrap();
+            note_tags(path)
+                .iter()
+                .any(|note_tag| tag_matches(note_tag, tag))
+        };
+        let filter: NoteFilter = if tag.is_some() {
+            Some(&tag_filter)
         } else {
-            println!("{} > {}", self.get_name(), folder.display());
+            None
+        };
+
+        FolderTree::build(
+            self.get_name(),
+            self.get_folder(),
+            &location,
+            item_type,
+            filter,
+            &self.get_note_types(),
+        )
+    }
+
+    pub fn search(&self, query: &str, is_regex: bool) -> Result<Vec<SearchHit>, Error> {
+        let pattern = build_pattern(query, is_regex)?;
+
+        let mut index = Index::load_root(&self.generate_vault_path(), self.get_note_types())?;
+        index.refresh()?;
+
+        let notes = if is_regex {
+            index.notes()
+        } else {
+            index.candidates(query)
+        };
+
+        Ok(search_notes(&pattern, notes, &self.generate_vault_path()))
+    }
+
+    pub fn backlinks(&self, name: &str) -> Result<Vec<Backlink>, Error> {
+        let vault_path = self.generate_vault_path();
+        let path = self.generate_note_path(name)?;
+
+        Ok(LinkResolver::new(&vault_path, self.get_note_types())?
+            .backlinks(path.strip_prefix(&vault_path).unwrap()))
+    }
+
+    pub fn check_links(&self) -> Result<LinkReport, Error> {
+        Ok(LinkResolver::new(&self.generate_vault_path(), self.get_note_types())?.check())
+    }
+
+    // @desc: Counts the notes in the vault for each tag, nested tags included.
+    pub fn tags(&self) -> Result<Vec<(String, usize)>, Error> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+
+        for note in walk_notes(&self.generate_vault_path(), &self.get_note_types())? {
+            for tag in note_tags(&note) {
+                *counts.entry(tag).or_default() += 1;
+            }
         }
 
-        let location = self.generate_location();
+        Ok(counts.into_iter().collect())
+    }
+
+    pub fn rebuild_index(&self) -> Result<usize, Error> {
+        Index::new(&self.generate_vault_path(), self.get_note_types()).rebuild()
+    }
+
+    pub fn load_journal(&self) -> Result<Journal, Error> {
+        Journal::load_path(&self.generate_vault_path().join(".jot/journal"), "")
+    }
+
+    // @desc: Reverses an operation from the vault's journal.
+    //
+    // @notes:
+    //      -> Created items are moved to trash rather than deleted.
+    //      -> Moves to other vaults are reversed by Vaults, since they involve the other vault.
+    pub fn undo(&mut self, operation: &Operation) -> Result<(), Error> {
+        let vault_path = self.generate_vault_path();
+
+        match operation {
+            Operation::Create { item, path } => {
+                let path_abs = vault_path.join(path);
+                if !path_abs.exists() {
+                    return Err(Error::ItemNotFound(
+                        item.to_owned(),
+                        path.display().to_string(),
+                    ));
+                }
+
+                let origin = path.parent().unwrap_or(Path::new("")).to_path_buf();
+                self.load_trash()?.add(item.to_owned(), &path_abs, origin)?;
+                self.update_index(|index| index.remove_path(&path_abs));
+            }
+            Operation::Remove { trash_id, .. } => {
+                self.restore_from_trash(trash_id)?;
+            }
+            Operation::Rename {
+                item,
+                path,
+                new_path,
+                update_links,
+            }
+            | Operation::Move {
+                item,
+                path,
+                new_path,
+                update_links,
+            } => self.move_back(item, path, new_path, *update_links)?,
+            Operation::ChangeFolder { folder, .. } => {
+                if !vault_path.join(folder).is_dir() {
+                    return Err(Error::PathNotFound);
+                }
+
+                self.set_folder(folder.to_owned())?;
+            }
+            Operation::ConfigSet { key, value, .. } => self.apply_config(key, value)?,
+            Operation::VMove { .. }
+            | Operation::AddVault { .. }
+            | Operation::ForgetVault { .. }
+            | Operation::Relink { .. }
+            | Operation::Undo { .. } => (),
+        }
+
+        Ok(())
+    }
+
+    // @desc: Moves an item from new_path back to path, to reverse a rename or a move.
+    fn move_back(
+        &self,
+        item: &Item,
+        path: &Path,
+        new_path: &Path,
+        update_links: bool,
+    ) -> Result<(), Error> {
+        let vault_path = self.generate_vault_path();
+        let (path, new_path) = (vault_path.join(path), vault_path.join(new_path));
+
+        if path.exists() {
+            return Err(Error::ItemAlreadyExists(
+                item.to_owned(),
+                self.relative_path(&path).display().to_string(),
+            ));
+        }
+
+        let resolver = self.generate_link_resolver(update_links)?;
+        if let Err(error) = rename(&new_path, &path) {
+            return Err(match error.kind() {
+                std::io::ErrorKind::NotFound => Error::ItemNotFound(
+                    item.to_owned(),
+                    self.relative_path(&new_path).display().to_string(),
+                ),
+                _ => Error::Undefined(error),
+            });
+        }
+        self.update_index(|index| index.rename_path(&new_path, &path));
+
+        self.rewrite_links(resolver, &new_path, &path);
+        Ok(())
+    }
+
+    // @desc: Records an operation in the vault's journal.
+    //
+    // @notes:
+    //      -> Like the search index, the journal is secondary to the operation itself, so failing
+    //         to record doesn't fail the operation.
+    fn record(&self, operation: Operation) {
+        if let Ok(mut journal) = self.load_journal() {
+            journal.record(operation).ok();
+        }
+    }
+
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.generate_vault_path())
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+
+    // @desc: Creates a resolver over the vault as it is before an item is renamed or moved, if
+    //        links need to be updated afterwards.
+    fn generate_link_resolver(&self, update_links: bool) -> Result<Option<LinkResolver>, Error> {
+        if update_links {
+            Ok(Some(LinkResolver::new(
+                &self.generate_vault_path(),
+                self.get_note_types(),
+            )?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // @desc: Rewrites links to an item that was moved from path to new_path.
+    fn rewrite_links(
+        &self,
+        resolver: Option<LinkResolver>,
+        path: &Path,
+        new_path: &Path,
+    ) -> LinkUpdate {
+        if let Some(resolver) = resolver {
+            let vault_path = self.generate_vault_path();
+            let moves = resolver.moves(
+                path.strip_prefix(&vault_path).unwrap(),
+                new_path.strip_prefix(&vault_path).unwrap(),
+            );
+
+            resolver.rewrite_links(&moves)
+        } else {
+            LinkUpdate::default()
+        }
+    }
+
+    // @desc: Applies an update to the vault's search index.
+    //
+    // @notes:
+    //      -> The index is secondary to the notes themselves, so failing to load or update it
+    //         doesn't fail the operation. A stale index gets refreshed on the next search and a
+    //         corrupt one has to be rebuilt with "jt index rebuild".
+    fn update_index<F>(&self, update: F)
+    where
+        F: FnOnce(&mut Index) -> Result<(), Error>,
+    {
+        if let Ok(mut index) = Index::load_root(&self.generate_vault_path(), self.get_note_types())
+        {
+            update(&mut index).ok();
+        }
+    }
+
+    fn find_history_entry(
+        &self,
+        reference: &str,
+        index: usize,
+        kind: HistoryKind,
+    ) -> Result<PathBuf, Error> {
+        match self.history.get(index) {
+            Some(entry) if entry.get_kind() == &kind => Ok(entry.get_path().to_owned()),
+            _ => Err(Error::InvalidHistoryRef(reference.to_owned())),
+        }
+    }
+
+    // @desc: Creates a note at location with the given content, stamped with the time it was
+    //        created (if it's a markdown note).
+    fn create_note(&self, location: &Path, name: &str, mut note: Note) -> Result<PathBuf, Error> {
+        let path = create_item(Item::Nt, name, location, &self.get_note_types())?;
+        if is_markdown(&path) {
+            note.stamp_created();
+        }
+        note.write(&path)?;
 
-        if let Some(item_type) = item_type {
-            filtered_list(item_type, location)
+        self.update_index(|index| index.update_note(&path));
+        Ok(path)
+    }
+
+    // @desc: Renders a template into the content of a new note. Templates of the vault take
+    //        precedence over global ones.
+    //
+    // @notes:
+    //      -> Built-in variables are "title", "date", "time", "vault" and "folder". Custom
+    //         variables passed in vars can override them.
+    fn render_note(
+        &self,
+        name: &str,
+        folder: &Path,
+        template: &str,
+        vars: &[(String, String)],
+        global_templates_dir: &Path,
+    ) -> Result<Note, Error> {
+        let template_path = find_template(
+            template,
+            &[
+                self.generate_templates_dir(),
+                global_templates_dir.to_path_buf(),
+            ],
+        )?;
+
+        let now = Local::now();
+        let mut variables = BTreeMap::from([
+            ("title".to_string(), name.to_owned()),
+            ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+            ("time".to_string(), now.format("%H:%M").to_string()),
+            ("vault".to_string(), self.get_name().to_owned()),
+            ("folder".to_string(), folder.to_string_lossy().to_string()),
+        ]);
+        variables.extend(vars.iter().cloned());
+
+        render_template(&template_path, &variables)
+    }
+
+    fn generate_templates_dir(&self) -> PathBuf {
+        join_paths(vec![
+            self.generate_vault_path(),
+            PathBuf::from(".jot/templates"),
+        ])
+    }
+
+    // @desc: Generates absolute path to a note (from its name or path), making sure it exists.
+    fn generate_note_path(&self, name: &str) -> Result<PathBuf, Error> {
+        let (location, item_name) = self.split_item_path(name)?;
+        let path = generate_item_path(&Item::Nt, &item_name, &location, &self.get_note_types())?;
+
+        if !path.exists() {
+            return Err(Error::ItemNotFound(Item::Nt, name.to_owned()));
+        }
+
+        Ok(path)
+    }
+
+    // @desc: Splits the name of an item, which can also be a path, into the absolute location of
+    //        the item and its own name.
+    //
+    // @notes:
+    //      -> Paths starting with "/" are relative to the root of the vault, others to the current
+    //         folder. Locations outside the vault aren't allowed.
+    //      -> "." and ".." are resolved without touching the fs, since the location may not exist
+    //         yet (e.g. when creating items with "--parents"). Symlinks are then checked by
+    //         resolving the deepest part of the location that does exist.
+    //      -> Every folder in the path has to have a valid name, and jot's own ".jot" folder can't
+    //         be reached.
+    //
+    // @example:
+    //      @conditions:
+    //          -> current folder: "folder1"
+    //          -> name: "../folder2/note"
+    //
+    //      @returns:
+    //          -> (<vault path>/folder2 [PathBuf], "note" [String])
+    fn split_item_path(&self, name: &str) -> Result<(PathBuf, String), Error> {
+        let path = Path::new(name);
+        let mut components: Vec<Component> = path.components().collect();
+
+        let item_name = match components.pop() {
+            Some(Component::Normal(item_name)) if item_name != ".jot" => {
+                item_name.to_string_lossy().to_string()
+            }
+            _ => return Err(Error::InvalidName),
+        };
+
+        let mut location = if path.has_root() {
+            PathBuf::new()
         } else {
-            rec_list(vec![true], location);
+            self.folder.to_owned()
+        };
+
+        for component in components {
+            match component {
+                Component::Normal(folder) => {
+                    if folder == ".jot" || !valid_name(&folder.to_string_lossy()) {
+                        return Err(Error::InvalidName);
+                    }
+                    location.push(folder)
+                }
+                Component::ParentDir => {
+                    if !location.pop() {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+                Component::CurDir | Component::RootDir | Component::Prefix(_) => (),
+            }
+        }
+
+        let location = self.generate_vault_path().join(location);
+        self.check_bounds(&location)?;
+
+        Ok((location, item_name))
+    }
+
+    // @desc: Checks that a location stays inside the vault once symlinks are resolved.
+    //
+    // @notes:
+    //      -> The location may not exist yet, so its deepest existing folder is what's checked.
+    fn check_bounds(&self, location: &Path) -> Result<(), Error> {
+        let vault_path = resolve_path(&self.generate_vault_path())?;
+
+        match location.ancestors().find(|folder| folder.exists()) {
+            Some(folder) if resolve_path(folder)?.starts_with(vault_path) => Ok(()),
+            _ => Err(Error::OutOfBounds),
+        }
+    }
+
+    // @desc: Creates the folders of location that don't exist yet, outermost first, recording
+    //        each of them so that they can be undone too.
+    fn create_parents(&self, location: &Path) -> Result<(), Error> {
+        let missing: Vec<&Path> = location
+            .ancestors()
+            .take_while(|folder| !folder.exists())
+            .collect();
+
+        for folder in missing.into_iter().rev() {
+            if let Err(error) = create_dir(folder) {
+                return Err(Error::Undefined(error));
+            }
+
+            self.record(Operation::Create {
+                item: Item::Fd,
+                path: self.relative_path(folder),
+            });
         }
+
+        Ok(())
+    }
+
+    // @desc: Generates absolute path to the root of the vault.
+    fn generate_vault_path(&self) -> PathBuf {
+        join_paths(vec![self.get_location().to_str().unwrap(), self.get_name()])
     }
 
     // @desc: Generates absoulte location for where jot is pointing currently.
@@ -228,3 +1127,71 @@ impl Vault {
         ])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env::temp_dir, fs};
+
+    fn create_vault(name: &str) -> Vault {
+        let location = resolve_path(&temp_dir())
+            .unwrap()
+            .join(format!("jot vault test {name} {}", std::process::id()));
+        let vault_path = location.join(name);
+        if location.exists() {
+            fs::remove_dir_all(&location).unwrap();
+        }
+
+        for folder in [".jot", "a", "b"] {
+            fs::create_dir_all(vault_path.join(folder)).unwrap();
+        }
+
+        let mut vault = Vault::default();
+        vault.set_path_data(name.to_owned(), location).unwrap();
+        vault
+    }
+
+    fn folder_history(vault: &Vault) -> Vec<PathBuf> {
+        vault
+            .get_history()
+            .iter()
+            .filter(|entry| entry.get_kind() == &HistoryKind::Folder)
+            .map(|entry| entry.get_path().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn change_folder_stores_history() {
+        let mut vault = create_vault("history");
+
+        vault.change_folder(&PathBuf::from("a")).unwrap();
+        vault.change_folder(&PathBuf::from("../b")).unwrap();
+
+        let stored = Vault::load_path(vault.path()).unwrap();
+        fs::remove_dir_all(vault.get_location()).unwrap();
+
+        assert_eq!(stored.get_folder(), &PathBuf::from("b"));
+        assert_eq!(
+            folder_history(&stored),
+            vec![PathBuf::from("a"), PathBuf::new()]
+        );
+    }
+
+    #[test]
+    fn change_folder_goes_back_to_the_previous_folder() {
+        let mut vault = create_vault("previous");
+
+        vault.change_folder(&PathBuf::from("a")).unwrap();
+        vault.change_folder(&PathBuf::from("../b")).unwrap();
+
+        let mut vault = Vault::load_path(vault.path()).unwrap();
+        vault.change_folder(&PathBuf::from("-")).unwrap();
+        let back = vault.get_folder().to_owned();
+        vault.change_folder(&PathBuf::from("-")).unwrap();
+        let forth = vault.get_folder().to_owned();
+        fs::remove_dir_all(vault.get_location()).unwrap();
+
+        assert_eq!(back, PathBuf::from("a"));
+        assert_eq!(forth, PathBuf::from("b"));
+    }
+}
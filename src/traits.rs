@@ -1,63 +1,360 @@
+use crate::output::error::Error;
+use chrono::Local;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     fmt::Debug,
-    fs::{create_dir_all, read_to_string, File},
+    fs::{copy, create_dir_all, read_to_string, remove_file, rename, File},
     io::{ErrorKind, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    process,
 };
+use toml::{value::Table, Value};
 
+// Decides whether a file that couldn't be parsed should be backed up (to the given path) and
+// regenerated with defaults, e.g. by asking the user.
+pub type Confirm<'a> = &'a dyn Fn(&Error, &Path) -> bool;
+
+// @notes:
+//      -> Every file has a "version" key, which is bumped whenever the layout of the file changes
+//         in a way serde can't handle on its own (e.g. a new field without a default). Files
+//         without it are at version 0.
 pub trait FileIO: Debug + Default + Serialize + DeserializeOwned {
+    // Version of the file this build of jot reads and writes.
+    const VERSION: u32;
+
     fn path(&self) -> PathBuf;
 
-    fn load() -> Self {
+    // @desc: Upgrades the data of a file from the given version to the next one.
+    //
+    // @notes:
+    //      -> Migrations only need to be written for versions that change more than the version
+    //         key, which is bumped by parse() itself.
+    fn migrate(_version: u32, _data: &mut Table) {}
+
+    fn load(confirm: Confirm) -> Result<Self, Error> {
         let path = <Self as FileIO>::path(&Self::default());
 
-        <Self as FileIO>::load_path(path)
+        <Self as FileIO>::load_or_recover(path, confirm)
     }
 
-    fn load_path(path: PathBuf) -> Self {
+    fn load_path(path: PathBuf) -> Result<Self, Error> {
         match read_to_string(&path) {
             Ok(file_string) => {
-                if let Ok(file_data) = toml::from_str::<Self>(&file_string) {
-                    file_data
-                } else {
-                    panic!("couldn't parse data")
+                let (file_data, migrated) = <Self as FileIO>::parse(&path, &file_string)?;
+
+                // The file is backed up before it's overwritten with the migrated data, in case
+                // the migration goes wrong.
+                if migrated {
+                    if let Err(err) = copy(&path, backup_path(&path)) {
+                        return Err(io_error(err, &path));
+                    }
+
+                    let _lock = lock_file(&path)?;
+                    write_atomic(&path, &file_data)?;
                 }
+
+                Ok(file_data)
             }
             Err(ref err) if err.kind() == ErrorKind::NotFound => {
                 <Self as FileIO>::create_file(path)
             }
-            Err(_) => panic!("couldn't load file"),
+            Err(err) => Err(io_error(err, &path)),
         }
     }
 
-    fn store(&self) {
-        let path = <Self as FileIO>::path(self);
+    // @desc: Parses the content of a file, migrating it step by step first if it was written by
+    //        an older version of jot. Also tells whether it was migrated.
+    //
+    // @notes:
+    //      -> Files of newer versions aren't read at all, since they could be damaged when stored
+    //         again.
+    fn parse(path: &Path, file_string: &str) -> Result<(Self, bool), Error> {
+        let mut data = match toml::from_str::<Table>(file_string) {
+            Ok(data) => data,
+            Err(error) => return Err(unparsable(path, error)),
+        };
+
+        let version = match data.get("version") {
+            None => 0,
+            Some(Value::Integer(version)) => match u32::try_from(*version) {
+                Ok(version) => version,
+                Err(_) => return Err(Error::FileUnparsable(path.to_path_buf(), None)),
+            },
+            Some(_) => return Err(Error::FileUnparsable(path.to_path_buf(), None)),
+        };
+
+        if version > Self::VERSION {
+            return Err(Error::UnsupportedVersion(path.to_path_buf(), version));
+        }
+
+        if version == Self::VERSION {
+            return match toml::from_str::<Self>(file_string) {
+                Ok(file_data) => Ok((file_data, false)),
+                Err(error) => Err(unparsable(path, error)),
+            };
+        }
+
+        for version in version..Self::VERSION {
+            <Self as FileIO>::migrate(version, &mut data);
+        }
+        data.insert("version".to_string(), Value::Integer(Self::VERSION.into()));
+
+        match Value::Table(data).try_into::<Self>() {
+            Ok(file_data) => Ok((file_data, true)),
+            Err(_) => Err(Error::FileUnparsable(path.to_path_buf(), None)),
+        }
+    }
+
+    // @desc: Same as load_path(), except that a file that can't be parsed is backed up and
+    //        regenerated with defaults, if confirm agrees to it.
+    fn load_or_recover(path: PathBuf, confirm: Confirm) -> Result<Self, Error> {
+        match <Self as FileIO>::load_path(path.to_owned()) {
+            Err(error @ Error::FileUnparsable(..)) => {
+                let backup = backup_path(&path);
+
+                if !confirm(&error, &backup) {
+                    return Err(error);
+                }
+
+                if let Err(err) = rename(&path, &backup) {
+                    return Err(io_error(err, &path));
+                }
 
-        let mut file = File::options()
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .unwrap();
+                <Self as FileIO>::create_file(path)
+            }
+            result => result,
+        }
+    }
+
+    // @notes:
+    //      -> Unlike create_file(), missing parent folders aren't created, since they most likely
+    //         belong to a vault that was moved or deleted outside of jot.
+    fn store(&self) -> Result<(), Error> {
+        let path = <Self as FileIO>::path(self);
+        let _lock = lock_file(&path)?;
 
-        <Self as FileIO>::write_file(&mut file, self)
+        write_atomic(&path, self)
     }
 
-    fn create_file(path: PathBuf) -> Self {
-        create_dir_all(path.parent().unwrap()).unwrap();
+    // @desc: Reloads the file, applies the change to it and stores it, all while holding the lock
+    //        of the file, so that concurrent invocations don't overwrite each other's changes.
+    //
+    // @notes:
+    //      -> If the file no longer exists, the change is applied to the data in memory.
+    //      -> A file that can't be parsed anymore isn't overwritten, its error is returned instead.
+    fn modify<F>(&mut self, change: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        let path = <Self as FileIO>::path(self);
+        let _lock = lock_file(&path)?;
+
+        match read_to_string(&path) {
+            Ok(file_string) => *self = <Self as FileIO>::parse(&path, &file_string)?.0,
+            Err(ref err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => return Err(io_error(err, &path)),
+        }
+
+        change(self)?;
+        write_atomic(&path, self)
+    }
 
-        let mut file = File::options().create(true).write(true).open(path).unwrap();
+    fn create_file(path: PathBuf) -> Result<Self, Error> {
+        if let Some(parent) = path.parent() {
+            if let Err(err) = create_dir_all(parent) {
+                return Err(io_error(err, parent));
+            }
+        }
 
         let data = Self::default();
 
-        <Self as FileIO>::write_file(&mut file, &data);
+        let _lock = lock_file(&path)?;
+        write_atomic(&path, &data)?;
 
-        data
+        Ok(data)
     }
+}
+
+// @desc: Takes an exclusive advisory lock on the file, which is released when the returned handle
+//        is dropped.
+//
+// @notes:
+//      -> The lock is held on a separate "<file>.lock" file, since the file itself is replaced
+//         on every write. Lock files are never deleted, as that would let two invocations lock
+//         different files.
+//      -> Reads don't take the lock, since writes are atomic and readers always see a whole file.
+fn lock_file(path: &Path) -> Result<File, Error> {
+    let lock_path = with_suffix(path, "lock");
+
+    let lock = match File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+    {
+        Ok(lock) => lock,
+        Err(err) => return Err(io_error(err, &lock_path)),
+    };
 
-    fn write_file(file: &mut File, data: &Self) {
-        let data_string = toml::to_string_pretty(&data).unwrap();
+    if let Err(err) = lock.lock() {
+        return Err(io_error(err, &lock_path));
+    }
+
+    Ok(lock)
+}
+
+// @desc: Writes data to a temporary file next to the file and renames it over the file, so that
+//        the file is never left empty or half written.
+fn write_atomic<T: Serialize>(path: &Path, data: &T) -> Result<(), Error> {
+    let data_string = match toml::to_string_pretty(data) {
+        Ok(data_string) => data_string,
+        Err(err) => {
+            return Err(Error::Undefined(std::io::Error::new(
+                ErrorKind::InvalidData,
+                err,
+            )))
+        }
+    };
 
-        file.write_all(data_string.as_bytes()).unwrap();
+    let temp_path = with_suffix(path, &format!("{}.tmp", process::id()));
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(data_string.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| rename(&temp_path, path));
+
+    if let Err(err) = result {
+        let _ = remove_file(&temp_path);
+        return Err(io_error(err, path));
+    }
+
+    Ok(())
+}
+
+// @desc: Appends a suffix to the file name, e.g. "config.lock" for "config".
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{file_name}.{suffix}"))
+}
+
+fn unparsable(path: &Path, error: toml::de::Error) -> Error {
+    let line = error.line_col().map(|(line, _)| line + 1);
+    Error::FileUnparsable(path.to_path_buf(), line)
+}
+
+// @desc: Translates io errors of data files to native errors.
+fn io_error(error: std::io::Error, path: &Path) -> Error {
+    match error.kind() {
+        ErrorKind::PermissionDenied => Error::PermissionDenied(path.to_path_buf()),
+        ErrorKind::NotFound => Error::MissingParent(path.to_path_buf()),
+        _ => Error::Undefined(error),
+    }
+}
+
+// @desc: Generates the path a file is backed up to before it's regenerated, e.g.
+//        "config.20261018-111900.bak" for "config".
+fn backup_path(path: &Path) -> PathBuf {
+    let time = Local::now().format("%Y%m%d-%H%M%S");
+    with_suffix(path, &format!("{time}.bak"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestFile {
+        version: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    impl Default for TestFile {
+        fn default() -> Self {
+            TestFile {
+                version: Self::VERSION,
+                name: String::new(),
+                tags: vec![],
+            }
+        }
+    }
+
+    impl FileIO for TestFile {
+        const VERSION: u32 = 2;
+
+        fn path(&self) -> PathBuf {
+            PathBuf::from("test")
+        }
+
+        // @notes:
+        //      -> 0 -> 1: "title" was renamed to "name".
+        //      -> 1 -> 2: "tags" was added.
+        fn migrate(version: u32, data: &mut Table) {
+            match version {
+                0 => {
+                    if let Some(title) = data.remove("title") {
+                        data.insert("name".to_string(), title);
+                    }
+                }
+                1 => {
+                    data.entry("tags").or_insert_with(|| Value::Array(vec![]));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_migrates_unversioned_files() {
+        let (file, migrated) = TestFile::parse(Path::new("test"), "title = 'notes'\n").unwrap();
+
+        assert!(migrated);
+        assert_eq!(
+            file,
+            TestFile {
+                version: 2,
+                name: "notes".to_string(),
+                tags: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_reads_current_files_as_they_are() {
+        let content = "version = 2\nname = 'notes'\ntags = ['a']\n";
+        let (file, migrated) = TestFile::parse(Path::new("test"), content).unwrap();
+
+        assert!(!migrated);
+        assert_eq!(
+            file,
+            TestFile {
+                version: 2,
+                name: "notes".to_string(),
+                tags: vec!["a".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_newer_files() {
+        let content = "version = 3\nname = 'notes'\n";
+
+        assert!(matches!(
+            TestFile::parse(Path::new("test"), content),
+            Err(Error::UnsupportedVersion(_, 3))
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_versions() {
+        for content in ["version = -1\n", "version = 'two'\n", "version = [\n"] {
+            assert!(matches!(
+                TestFile::parse(Path::new("test"), content),
+                Err(Error::FileUnparsable(..))
+            ));
+        }
     }
 }
@@ -1,12 +1,15 @@
 use crate::{
-    enums::{Item, VaultItem},
+    enums::Item,
+    notes::{fuzzy::NoteMatch, types::NoteTypes},
     output::error::Error,
+    state::{editor::Editor, vaults::doctor::Problem},
 };
 use dunce::canonicalize;
 use fs_extra::{dir::CopyOptions, move_items};
 use std::{
     env::consts::OS,
-    fs::{remove_dir_all, remove_file, rename, DirBuilder, File},
+    fs::{rename, DirBuilder, File},
+    io::stdin,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -31,8 +34,13 @@ pub fn resolve_path(path: &Path) -> Result<PathBuf, Error> {
     }
 }
 
-pub fn create_item(item_type: Item, name: &str, location: &Path) -> Result<PathBuf, Error> {
-    let path = generate_item_path(&item_type, name, location)?;
+pub fn create_item(
+    item_type: Item,
+    name: &str,
+    location: &Path,
+    note_types: &NoteTypes,
+) -> Result<PathBuf, Error> {
+    let path = generate_item_path(&item_type, name, location, note_types)?;
 
     if let Err(error) = create_item_collect(&item_type, &path) {
         return Err(match error.kind() {
@@ -47,31 +55,32 @@ pub fn create_item(item_type: Item, name: &str, location: &Path) -> Result<PathB
     Ok(path)
 }
 
-pub fn remove_item(item_type: Item, name: &str, location: &Path) -> Result<(), Error> {
-    let path = generate_item_path(&item_type, name, location)?;
-
-    if let Err(error) = remove_item_collect(&item_type, &path) {
-        return Err(match error.kind() {
-            std::io::ErrorKind::NotFound => Error::ItemNotFound(item_type, name.to_owned()),
-            _ => Error::Undefined(error),
-        });
-    }
-
-    Ok(())
-}
-
+// @notes:
+//      -> Notes keep their extension unless the new name ends in another note extension.
 pub fn rename_item(
     item_type: Item,
     name: &str,
     new_name: &str,
     location: &Path,
+    note_types: &NoteTypes,
 ) -> Result<PathBuf, Error> {
     if new_name == name {
         return Err(Error::SameName);
     }
 
-    let original_path = generate_item_path(&item_type, name, location)?;
-    let new_path = generate_item_path(&item_type, new_name, location)?;
+    let original_path = generate_item_path(&item_type, name, location, note_types)?;
+    let new_path = match original_path.extension() {
+        Some(extension)
+            if matches!(item_type, Item::Nt) && !note_types.is_note(Path::new(new_name)) =>
+        {
+            let new_name = note_types.add_extension(new_name, &extension.to_string_lossy())?;
+            generate_item_path(&item_type, &new_name, location, note_types)?
+        }
+        _ => generate_item_path(&item_type, new_name, location, note_types)?,
+    };
+    if new_path == original_path {
+        return Err(Error::SameName);
+    }
 
     if let Err(error) = rename(original_path, &new_path) {
         return Err(match error.kind() {
@@ -88,31 +97,39 @@ pub fn move_item(
     name: &str,
     original_location: &PathBuf,
     new_location: &Path,
+    note_types: &NoteTypes,
 ) -> Result<PathBuf, Error> {
     if new_location == original_location {
         return Err(Error::SameLocation);
     }
 
-    let new_path = generate_item_path(&item_type, name, new_location)?;
+    // The item keeps its file name (and so its extension) in the new location.
+    let original_path = generate_item_path(&item_type, name, original_location, note_types)?;
+    let new_path = new_location.join(original_path.file_name().unwrap());
     if new_path.exists() {
         return Err(Error::ItemAlreadyExists(item_type, name.to_owned()));
     }
 
-    let original_path = vec![generate_item_path(&item_type, name, original_location)?];
-    move_items(&original_path, new_location, &CopyOptions::new())?;
+    move_items(&[original_path], new_location, &CopyOptions::new())?;
 
     Ok(new_path)
 }
 
-pub fn open_note(editor_data: (&String, bool), name: &str, location: &Path) -> Result<(), Error> {
-    let path = generate_item_path(&Item::Nt, name, location)?;
+pub fn open_note(
+    editor: &Editor,
+    line: Option<usize>,
+    name: &str,
+    location: &Path,
+    note_types: &NoteTypes,
+) -> Result<PathBuf, Error> {
+    let path = generate_item_path(&Item::Nt, name, location, note_types)?;
 
     if !path.exists() {
         return Err(Error::ItemNotFound(Item::Nt, name.to_string()));
     }
 
-    run_editor(editor_data, &path)?;
-    Ok(())
+    editor.open(&path, line)?;
+    Ok(path)
 }
 
 pub fn open_folder(location: &Path) -> Result<(), Error> {
@@ -130,124 +147,105 @@ pub fn open_folder(location: &Path) -> Result<(), Error> {
     }
 }
 
-pub fn run_editor(editor_data: (&String, bool), path: &Path) -> Result<(), Error> {
-    let (editor, conflict) = editor_data;
+// @desc: Asks the user whether a data file that couldn't be parsed should be backed up and
+//        regenerated with defaults.
+pub fn confirm_recovery(error: &Error, backup: &Path) -> bool {
+    eprint!(
+        "\x1b[0;31merror\x1b[0m: {error}\nback up the file to {} and regenerate it with defaults? [y/N] ",
+        backup.display()
+    );
 
-    if let Err(error) = run_editor_collect(editor, conflict, path) {
-        return Err(match error.kind() {
-            std::io::ErrorKind::NotFound => Error::EditorNotFound,
-            _ => Error::Undefined(error),
-        });
-    }
-
-    Ok(())
+    read_confirmation()
 }
 
-pub fn filtered_list(item_type: &VaultItem, path: PathBuf) {
-    let mut filtered_entries: Vec<String> = vec![];
+// @desc: Asks the user whether a problem found by "jt doctor" should be fixed.
+pub fn confirm_fix(problem: &Problem) -> bool {
+    eprint!(
+        "\x1b[0;31m✗\x1b[0m {problem}\n{}? [y/N] ",
+        problem.fix().unwrap_or_default()
+    );
 
-    for entry in path.read_dir().unwrap() {
-        let entry = entry.unwrap().path();
-        let entry_name = entry.file_stem().unwrap().to_str().unwrap();
+    read_confirmation()
+}
 
-        match item_type {
-            VaultItem::Folder | VaultItem::Fd => {
-                if entry.is_dir() {
-                    filtered_entries.push(entry_name.to_string())
-                }
-            }
-            _ => {
-                if entry.is_file() && entry.extension().unwrap() == "md" {
-                    filtered_entries.push(format!("\x1b[0;34m{entry_name}\x1b[0m"));
-                }
-            }
-        }
+// @desc: Asks the user which of several notes matching a query should be opened. Returns None if
+//        the answer isn't one of the numbers offered.
+pub fn pick_note(query: &str, candidates: &[NoteMatch]) -> Option<usize> {
+    eprintln!("several notes match {query}:");
+    for (index, candidate) in candidates.iter().enumerate() {
+        eprintln!("  {} \t {candidate}", index + 1);
     }
+    eprint!("open which one? [1-{}] ", candidates.len());
 
-    for (index, entry) in filtered_entries.iter().enumerate() {
-        if filtered_entries.len() - index == 1 {
-            print!("└── ")
-        } else {
-            print!("├── ")
-        }
-
-        println!("{entry}");
+    match read_answer()?.parse::<usize>() {
+        Ok(number) if (1..=candidates.len()).contains(&number) => Some(number - 1),
+        _ => None,
     }
 }
 
-// @desc: Recursively goes over the contained elements in a folder, then prints the folder's 
-//        tree.
-//
-// @notes:
-//      -> The value in "level" defines how deep into the tree we are.
-//      -> "were_last" is a vector of booleans whose length at any moment is equal to the 
-//         current level + 1. Everytime a folder is traversed, a boolean represening if it
-//         was the last element in its parent folder or not is added to the vector. This 
-//         helps in determining when to print a pipe for elements not part of current parent
-//         folder.
-pub fn rec_list(mut were_last: Vec<bool>, path: PathBuf) -> Vec<bool> {
-    let length = path.read_dir().unwrap().count();
-
-    for (count, entry) in path.read_dir().unwrap().enumerate() {
-        let entry = entry.unwrap().path();
-        let entry_name = entry.file_stem().unwrap().to_str().unwrap();
-
-        if entry_name == ".jot" {
-            continue;
-        }
+fn read_confirmation() -> bool {
+    read_answer().is_some_and(|answer| matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
 
-        if let Some(extension) = entry.extension() {
-            if entry.is_file() && extension != "md" {
-                continue;
-            }
-        }
+// @desc: Reads a line of input from the user, trimmed. Returns None if stdin can't be read.
+fn read_answer() -> Option<String> {
+    let mut answer = String::new();
+    if stdin().read_line(&mut answer).is_err() {
+        return None;
+    }
 
-        let is_last = length - count == 1;
+    Some(answer.trim().to_owned())
+}
 
-        for level in 0..were_last.len() - 1 {
-            if were_last[level + 1] {
-                print!("    ")
-            } else {
-                print!("│   ")
-            }
-        }
+// Optional check a note has to pass to be listed, e.g. having a certain tag.
+pub type NoteFilter<'a> = Option<&'a dyn Fn(&Path) -> bool>;
 
-        if is_last {
-            print!("└── ")
-        } else {
-            print!("├── ")
-        }
+// @desc: Recursively collects paths to all notes inside a folder, skipping the ".jot" folder.
+//
+// @notes:
+//      -> Paths are sorted so that results stay stable across runs.
+pub fn walk_notes(path: &Path, note_types: &NoteTypes) -> Result<Vec<PathBuf>, Error> {
+    let mut notes = walk_files(path)?;
+    notes.retain(|note| note_types.is_note(note));
+    Ok(notes)
+}
 
-        if entry.is_dir() {
-            println!("{entry_name}");
+// @desc: Recursively collects paths to all files inside a folder, notes or not, skipping the
+//        ".jot" folder.
+//
+// @notes:
+//      -> Symlinks aren't followed, which keeps links to folders outside of the vault out of it and
+//         loops from recursing forever.
+pub fn walk_files(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
 
-            were_last.push(is_last);
-            were_last = rec_list(were_last, entry);
-            were_last.pop();
-        } else {
-            println!("\x1b[0;34m{entry_name}\x1b[0m",);
-        }
+    if let Err(error) = walk_files_collect(path, &mut files) {
+        return Err(Error::Undefined(error));
     }
 
-    were_last
+    files.sort();
+    Ok(files)
 }
 
-fn valid_name(name: &str) -> bool {
+pub fn valid_name(name: &str) -> bool {
     name.chars().all(|char| !r#"\/?%*:|"<>"#.contains(char))
 }
 
-fn generate_item_path(item_type: &Item, name: &str, location: &Path) -> Result<PathBuf, Error> {
+pub fn generate_item_path(
+    item_type: &Item,
+    name: &str,
+    location: &Path,
+    note_types: &NoteTypes,
+) -> Result<PathBuf, Error> {
     if !valid_name(name) {
         return Err(Error::InvalidName);
     }
 
-    let mut path = join_paths(vec![location.to_str().unwrap(), name]);
-
     if let Item::Nt = item_type {
-        path.set_extension("md");
+        Ok(note_types.note_path(location, name))
+    } else {
+        Ok(join_paths(vec![location.to_str().unwrap(), name]))
     }
-
-    Ok(path)
 }
 
 // All "_collect" functions below are meant to collect errors from all possible routes a
@@ -264,21 +262,55 @@ fn create_item_collect(item_type: &Item, path: &Path) -> Result<(), std::io::Err
     Ok(())
 }
 
-fn remove_item_collect(item_type: &Item, path: &Path) -> Result<(), std::io::Error> {
-    if let Item::Nt = item_type {
-        remove_file(path)?;
-    } else {
-        remove_dir_all(path)?;
-    }
+// @desc: Collects the entries of a folder that should be listed, i.e. notes and folders other than
+//        ".jot".
+//
+// @notes:
+//      -> When a filter is passed, only the notes that pass it are collected. Folders are always
+//         collected, use "contains_notes" to tell whether they hold any note that passes it.
+pub fn list_entries(path: &Path, filter: NoteFilter, note_types: &NoteTypes) -> Vec<PathBuf> {
+    path.read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|entry| {
+            if entry.file_name().unwrap() == ".jot" {
+                false
+            } else if entry.is_dir() {
+                true
+            } else {
+                note_types.is_note(entry) && filter.is_none_or(|filter| filter(entry))
+            }
+        })
+        .collect()
+}
 
-    Ok(())
+// @desc: Checks if a folder holds any note that passes the filter, at any depth.
+//
+// @notes:
+//      -> Stops at the first such note, so every note is read at most once.
+pub fn contains_notes(path: &Path, filter: NoteFilter, note_types: &NoteTypes) -> bool {
+    list_entries(path, filter, note_types)
+        .iter()
+        .any(|entry| !entry.is_dir() || contains_notes(entry, filter, note_types))
 }
 
-fn run_editor_collect(editor: &str, conflict: bool, path: &Path) -> Result<(), std::io::Error> {
-    let mut cmd = Command::new(editor).arg(path.to_str().unwrap()).spawn()?;
+fn walk_files_collect(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), std::io::Error> {
+    for entry in path.read_dir()? {
+        let entry = entry?.path();
+
+        if entry.file_name().unwrap() == ".jot" {
+            continue;
+        }
+
+        let file_type = entry.symlink_metadata()?.file_type();
 
-    if conflict {
-        cmd.wait()?;
+        if file_type.is_symlink() {
+            continue;
+        } else if file_type.is_dir() {
+            walk_files_collect(&entry, files)?;
+        } else {
+            files.push(entry);
+        }
     }
 
     Ok(())